@@ -1,5 +1,9 @@
 #!/usr/bin/env sh
+# Builds the light client for the page (`pkg`) and for Node.js (`pkg-node`), then serves the page.
 set -e -x
 cargo +nightly build --release -p labor-node --target wasm32-unknown-unknown --no-default-features --features browser -Z features=itarget
-wasm-bindgen ../../../../target/wasm32-unknown-unknown/release/labor_node.wasm --out-dir pkg --target web
-python -m http.server 8000
+wasm-bindgen ../../../target/wasm32-unknown-unknown/release/labor_node.wasm --out-dir pkg --target web
+wasm-bindgen ../../../target/wasm32-unknown-unknown/release/labor_node.wasm --out-dir pkg-node --target nodejs
+if [ "$1" != "--no-serve" ]; then
+	python3 -m http.server 8000
+fi
@@ -0,0 +1,97 @@
+// Runs the light client under Node.js against a local development node: submits a job posting
+// through the client and waits until `getJob` and `listOpenJobs` return the job.
+//
+// Build with `npm run build` and the node with `cargo build --release` first. `LABOR_NODE` may
+// point to another node binary.
+
+const { execFileSync, spawn } = require('child_process');
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const { performance } = require('perf_hooks');
+
+// What the client expects from a browser: a WebSocket transport and IndexedDB for its database.
+global.window = global;
+global.performance = global.performance || performance;
+global.WebSocket = require('ws');
+require('fake-indexeddb/auto');
+
+const NODE =
+	process.env.LABOR_NODE || path.join(__dirname, '../../../../target/release/labor-node');
+const WS_P2P_PORT = 45946;
+// The peer id of `--node-key` 1.
+const PEER_ID = '12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp';
+const TIMEOUT_MS = 120000;
+
+function assert(condition, message) {
+	if (!condition) {
+		throw new Error(message);
+	}
+}
+
+// Calls `f` until it resolves to anything but `null` or `undefined`.
+async function eventually(what, f) {
+	const deadline = Date.now() + TIMEOUT_MS;
+	let last;
+	while (Date.now() < deadline) {
+		try {
+			const value = await f();
+			if (value !== null && value !== undefined) {
+				return value;
+			}
+		} catch (e) {
+			last = e;
+		}
+		await new Promise((resolve) => setTimeout(resolve, 1000));
+	}
+	throw new Error(`Timed out waiting for ${what}` + (last ? `: ${last}` : ''));
+}
+
+async function main() {
+	const basePath = fs.mkdtempSync(path.join(os.tmpdir(), 'labor-headless-'));
+	const stdio = ['ignore', 'pipe', 'ignore'];
+	const spec = JSON.parse(execFileSync(NODE, ['build-spec', '--dev', '--raw'], { stdio }));
+	spec.bootNodes = [`/ip4/127.0.0.1/tcp/${WS_P2P_PORT}/ws/p2p/${PEER_ID}`];
+	// Signed before the node starts, which locks its database.
+	const extrinsic = execFileSync(NODE, [
+		'labor', 'post', '--dev', '--suri', '//Alice', '--details', 'Paint the fence',
+		'--payment', '1000', '-d', basePath,
+	]).toString().trim();
+
+	const node = spawn(NODE, [
+		'--dev', '-d', basePath, '--node-key', '1'.padStart(64, '0'),
+		'--listen-addr', `/ip4/127.0.0.1/tcp/${WS_P2P_PORT}/ws`,
+		'--rpc-port', '45947', '--ws-port', '45948', '--no-prometheus',
+	], { stdio: 'ignore' });
+
+	try {
+		const { start_client } = require('../pkg-node/labor_node.js');
+		const client = await start_client(JSON.stringify(spec), 'info');
+
+		// The client forwards the extrinsic once it is connected and synced.
+		const hash = await eventually('the submission', () => client.submitSignedExtrinsic(extrinsic));
+		assert(/^0x[0-9a-f]{64}$/.test(hash), `unexpected extrinsic hash ${hash}`);
+
+		const job = await eventually('the job', () => client.getJob(0));
+		assert(job.id === 0, `unexpected job id ${job.id}`);
+		assert(job.details === 'Paint the fence', `unexpected details ${job.details}`);
+		assert(job.payment === '1000', `unexpected payment ${job.payment}`);
+		assert(job.status === 'Open', `unexpected status ${job.status}`);
+
+		const open = await client.listOpenJobs();
+		assert(open.some((job) => job.id === 0), `the job is not open: ${JSON.stringify(open)}`);
+		assert(await client.getJob(1) === null, 'there is no job 1');
+		console.log('Headless light client test passed');
+	} finally {
+		node.kill('SIGINT');
+		fs.rmSync(basePath, { recursive: true, force: true });
+	}
+}
+
+main().then(
+	() => process.exit(0),
+	(e) => {
+		console.error(e);
+		process.exit(1);
+	},
+);
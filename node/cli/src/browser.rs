@@ -16,19 +16,62 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::chain_spec::ChainSpec;
+use crate::{chain_spec::ChainSpec, labor_rpc::MAX_JOBS};
 use log::info;
-use wasm_bindgen::prelude::*;
+use serde_json::{json, Value};
+use std::{
+	cell::{Cell, RefCell},
+	future::Future,
+	rc::Rc,
+};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
 use browser_utils::{
 	Client,
 	browser_configuration, init_logging, set_console_error_panic_hook,
 };
 
+#[wasm_bindgen(typescript_custom_section)]
+const JOB: &'static str = r#"
+/** A job of the labor market. Accounts are SS58 addresses, balances decimal strings. */
+export interface Job {
+	id: number;
+	employer: string;
+	worker: string | null;
+	payment: string;
+	workerBond: string;
+	referrer: string | null;
+	status: "Open" | "Assigned" | "Completed" | "Cancelled" | "Disputed" | "Resolved";
+	details: string;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+	/// Resolves to a job or `null`.
+	#[wasm_bindgen(typescript_type = "Promise<Job | null>")]
+	pub type JobPromise;
+
+	/// Resolves to a list of jobs.
+	#[wasm_bindgen(typescript_type = "Promise<Job[]>")]
+	pub type JobsPromise;
+
+	/// Resolves to a hex encoded hash.
+	#[wasm_bindgen(typescript_type = "Promise<string>")]
+	pub type HashPromise;
+}
+
 /// Starts the client.
 #[wasm_bindgen]
-pub async fn start_client(chain_spec: Option<String>, log_level: String) -> Result<Client, JsValue> {
+pub async fn start_client(
+	chain_spec: Option<String>,
+	log_level: String,
+) -> Result<LaborClient, JsValue> {
 	start_inner(chain_spec, log_level)
 		.await
+		.map(|client| LaborClient {
+			rpc: Rpc { client: Rc::new(RefCell::new(client)), next_id: Rc::new(Cell::new(0)) },
+		})
 		.map_err(|err| JsValue::from_str(&err.to_string()))
 }
 
@@ -46,12 +89,9 @@ async fn start_inner(
 
 	let config = browser_configuration(chain_spec).await?;
 
-	info!("Substrate browser node");
-	info!("✌️  version {}", config.impl_version);
-	info!("❤️  by Parity Technologies, 2017-2021");
-	info!("📋 Chain specification: {}", config.chain_spec.name());
-	info!("🏷 Node name: {}", config.network.node_name);
-	info!("👤 Role: {:?}", config.role);
+	info!("Labor light client {}", config.impl_version);
+	info!("Chain specification: {}", config.chain_spec.name());
+	info!("Node name: {}", config.network.node_name);
 
 	// Create the service. This is the most heavy initialization step.
 	let (task_manager, rpc_handlers) =
@@ -61,3 +101,114 @@ async fn start_inner(
 
 	Ok(browser_utils::start_client(task_manager, rpc_handlers))
 }
+
+/// A light client of the labor chain.
+///
+/// Job data is read from storage proofs that the client checks itself, so it does not rely on
+/// the full nodes it connects to.
+#[wasm_bindgen]
+pub struct LaborClient {
+	rpc: Rpc,
+}
+
+/// Sends JSON-RPC requests to the client. Clones share the client, so a call can send further
+/// requests once the first one resolved.
+#[derive(Clone)]
+struct Rpc {
+	client: Rc<RefCell<Client>>,
+	next_id: Rc<Cell<u64>>,
+}
+
+#[wasm_bindgen]
+impl LaborClient {
+	/// The job with the id at the best block, `null` if there is none.
+	#[wasm_bindgen(js_name = getJob)]
+	pub fn get_job(&mut self, id: u32) -> JobPromise {
+		let job = self.rpc.call("labor_getJob", json!([id]));
+		future_to_promise(async move { to_js(&job.await?) }).unchecked_into()
+	}
+
+	/// The jobs at the best block that accept applications.
+	#[wasm_bindgen(js_name = listOpenJobs)]
+	pub fn list_open_jobs(&mut self) -> JobsPromise {
+		let rpc = self.rpc.clone();
+		future_to_promise(async move {
+			// All pages are read at the same block, so that no job is missed or listed twice
+			// when a new best block is imported in between.
+			let best = rpc.call("chain_getBlockHash", json!([])).await?;
+			let mut open = Vec::new();
+			let mut start = 0;
+			loop {
+				let page = rpc.call("labor_getJobs", json!([start, MAX_JOBS, best]));
+				let jobs = match page.await? {
+					Value::Array(jobs) => jobs,
+					jobs => return Err(error(&format!("Unexpected jobs: {}", jobs))),
+				};
+				let count = jobs.len() as u32;
+				open.extend(jobs.into_iter().filter(|job| job["status"] == "Open"));
+				// Jobs are never removed, so only the last page is short.
+				if count < MAX_JOBS {
+					break
+				}
+				start += MAX_JOBS;
+			}
+			to_js(&Value::Array(open))
+		})
+		.unchecked_into()
+	}
+
+	/// Submits a signed extrinsic, SCALE encoded as hex like the output of `labor-node tx`, and
+	/// resolves to its hash once it is in the transaction pool.
+	#[wasm_bindgen(js_name = submitSignedExtrinsic)]
+	pub fn submit_signed_extrinsic(&mut self, extrinsic: String) -> HashPromise {
+		let hash = self.rpc.call("author_submitExtrinsic", json!([extrinsic]));
+		future_to_promise(async move { to_js(&hash.await?) }).unchecked_into()
+	}
+
+	/// Sends a raw JSON-RPC request. Returns a `Promise` of the response.
+	#[wasm_bindgen(js_name = rpcSend)]
+	pub fn rpc_send(&mut self, rpc: &str) -> js_sys::Promise {
+		self.rpc.client.borrow_mut().rpc_send(rpc)
+	}
+
+	/// Subscribes with a raw JSON-RPC request, calling `callback` with every notification.
+	#[wasm_bindgen(js_name = rpcSubscribe)]
+	pub fn rpc_subscribe(&mut self, rpc: &str, callback: js_sys::Function) {
+		self.rpc.client.borrow_mut().rpc_subscribe(rpc, callback)
+	}
+}
+
+impl Rpc {
+	/// Sends a JSON-RPC request and resolves to its result.
+	fn call(&self, method: &str, params: Value) -> impl Future<Output = Result<Value, JsValue>> {
+		self.next_id.set(self.next_id.get() + 1);
+		let request = json!({
+			"jsonrpc": "2.0",
+			"id": self.next_id.get(),
+			"method": method,
+			"params": params,
+		});
+		let response = JsFuture::from(self.client.borrow_mut().rpc_send(&request.to_string()));
+		let method = method.to_owned();
+		async move {
+			// The client rejects with `null` when it stopped.
+			let response = response.await.map_err(|_| error("The client stopped"))?;
+			let response = response.as_string().ok_or_else(|| error("Invalid RPC response"))?;
+			let mut response: Value =
+				serde_json::from_str(&response).map_err(|e| error(&e.to_string()))?;
+			if let Some(e) = response.get("error") {
+				let message = e["message"].as_str().unwrap_or("unknown error");
+				return Err(error(&format!("{} failed: {}", method, message)))
+			}
+			Ok(response["result"].take())
+		}
+	}
+}
+
+fn to_js(value: &Value) -> Result<JsValue, JsValue> {
+	js_sys::JSON::parse(&value.to_string())
+}
+
+fn error(message: &str) -> JsValue {
+	js_sys::Error::new(message).into()
+}
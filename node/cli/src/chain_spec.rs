@@ -22,11 +22,17 @@ use sc_chain_spec::ChainSpecExtension;
 use sp_core::{Pair, Public, crypto::UncheckedInto, sr25519};
 use serde::{Serialize, Deserialize};
 use labor_node_runtime::{
-    AuthorityDiscoveryConfig, BabeConfig, BalancesConfig, ContractsConfig, CouncilConfig,
-    DemocracyConfig, GrandpaConfig, ImOnlineConfig, SessionConfig, SessionKeys, StakerStatus,
-    StakingConfig, ElectionsConfig, IndicesConfig, SocietyConfig, SudoConfig, SystemConfig,
-    TechnicalCommitteeConfig, wasm_binary_unwrap, MAX_NOMINATIONS,
+    AuthorityDiscoveryConfig, BabeConfig, BalancesConfig, CouncilConfig, DemocracyConfig,
+    GrandpaConfig, ImOnlineConfig, SessionConfig, SessionKeys, StakerStatus, StakingConfig,
+    ElectionsConfig, IndicesConfig, SystemConfig, TechnicalCommitteeConfig, wasm_binary_unwrap,
+    MAX_NOMINATIONS,
 };
+#[cfg(feature = "contracts")]
+use labor_node_runtime::ContractsConfig;
+#[cfg(feature = "society")]
+use labor_node_runtime::SocietyConfig;
+#[cfg(feature = "sudo")]
+use labor_node_runtime::SudoConfig;
 use labor_node_runtime::Block;
 use labor_node_runtime::constants::currency::*;
 use sc_service::ChainType;
@@ -37,7 +43,9 @@ use sp_consensus_babe::{AuthorityId as BabeId};
 use pallet_im_online::sr25519::{AuthorityId as ImOnlineId};
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use sp_runtime::{Perbill, traits::{Verify, IdentifyAccount}};
+use sp_core::hashing::blake2_256;
 use serde_json::map::Map;
+use codec::Encode;
 
 pub use node_primitives::{AccountId, Balance, Signature};
 pub use labor_node_runtime::GenesisConfig;
@@ -46,6 +54,9 @@ type AccountPublic = <Signature as Verify>::Signer;
 
 const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
+/// Seed of the genesis nominations of the built-in chain specs.
+pub const DEFAULT_NOMINATION_SEED: u64 = 0;
+
 /// Node `ChainSpec` extensions.
 ///
 /// Additional parameters for some Substrate core modules,
@@ -57,6 +68,17 @@ pub struct Extensions {
     pub fork_blocks: sc_client_api::ForkBlocks<Block>,
     /// Known bad block hashes.
     pub bad_blocks: sc_client_api::BadBlocks<Block>,
+    /// Interval in milliseconds at which GRANDPA gossips, 333 if not given.
+    pub grandpa_gossip_duration: Option<u64>,
+    /// Number of blocks between GRANDPA justifications, 512 if not given.
+    pub grandpa_justification_period: Option<u32>,
+}
+
+impl Extensions {
+    /// Try to get the extension from the given `ChainSpec`.
+    pub fn try_get(chain_spec: &dyn sc_service::ChainSpec) -> Option<&Self> {
+        sc_chain_spec::get_extension(chain_spec.extensions())
+    }
 }
 
 /// Specialized `ChainSpec`.
@@ -132,7 +154,14 @@ fn staging_testnet_config_genesis() -> GenesisConfig {
 
     let endowed_accounts: Vec<AccountId> = vec![root_key.clone()];
 
-    testnet_genesis(initial_authorities, vec![], root_key, Some(endowed_accounts), false)
+    testnet_genesis(
+        initial_authorities,
+        vec![],
+        #[cfg(feature = "sudo")] root_key,
+        Some(endowed_accounts),
+        #[cfg(feature = "contracts")] false,
+        DEFAULT_NOMINATION_SEED,
+    )
 }
 
 /// Staging testnet config.
@@ -192,6 +221,9 @@ pub fn authority_keys_from_seed(seed: &str) -> (
 }
 
 /// Helper function to create GenesisConfig for testing
+///
+/// The nominations of `initial_nominators` are drawn from `nomination_seed` and the nominator's
+/// account id, so the same inputs always give the same genesis.
 pub fn testnet_genesis(
     initial_authorities: Vec<(
         AccountId,
@@ -202,9 +234,10 @@ pub fn testnet_genesis(
         AuthorityDiscoveryId,
     )>,
     initial_nominators: Vec<AccountId>,
-    root_key: AccountId,
+    #[cfg(feature = "sudo")] root_key: AccountId,
     endowed_accounts: Option<Vec<AccountId>>,
-    enable_println: bool,
+    #[cfg(feature = "contracts")] enable_println: bool,
+    nomination_seed: u64,
 ) -> GenesisConfig {
     let mut endowed_accounts: Vec<AccountId> = endowed_accounts.unwrap_or_else(|| {
         vec![
@@ -230,14 +263,18 @@ pub fn testnet_genesis(
     });
 
     // stakers: all validators and nominators.
-    let mut rng = rand::thread_rng();
     let stakers = initial_authorities
         .iter()
         .map(|x| (x.0.clone(), x.1.clone(), STASH, StakerStatus::Validator))
         .chain(initial_nominators.iter().map(|x| {
-            use rand::{seq::SliceRandom, Rng};
+            use rand::{seq::SliceRandom, Rng, SeedableRng};
+            // Seeded per nominator, so adding a nominator leaves the others' nominations as
+            // they were. `u32` keeps the draws the same on 32 and 64 bit hosts.
+            let mut rng = rand_chacha::ChaChaRng::from_seed(
+                blake2_256(&(nomination_seed, x).encode()),
+            );
             let limit = (MAX_NOMINATIONS as usize).min(initial_authorities.len());
-            let count = rng.gen::<usize>() % limit;
+            let count = rng.gen::<u32>() as usize % limit;
             let nominations = initial_authorities
                 .as_slice()
                 .choose_multiple(&mut rng, count)
@@ -300,14 +337,17 @@ pub fn testnet_genesis(
                 .collect(),
             phantom: Default::default(),
         },
+        #[cfg(feature = "contracts")]
         pallet_contracts: ContractsConfig {
             // println should only be enabled on development chains
             current_schedule: pallet_contracts::Schedule::default()
                 .enable_println(enable_println),
         },
+        #[cfg(feature = "sudo")]
         pallet_sudo: SudoConfig {
             key: root_key,
         },
+        pallet_timing: Default::default(),
         pallet_babe: BabeConfig {
             authorities: vec![],
             epoch_config: Some(labor_node_runtime::BABE_GENESIS_EPOCH_CONFIG),
@@ -323,6 +363,7 @@ pub fn testnet_genesis(
         },
         pallet_membership_Instance1: Default::default(),
         pallet_treasury: Default::default(),
+        #[cfg(feature = "society")]
         pallet_society: SocietyConfig {
             members: endowed_accounts.iter()
                 .take((num_endowed_accounts + 1) / 2)
@@ -332,6 +373,7 @@ pub fn testnet_genesis(
             max_members: 999,
         },
         pallet_vesting: Default::default(),
+        #[cfg(feature = "gilt")]
         pallet_gilt: Default::default(),
     }
 }
@@ -342,9 +384,10 @@ fn development_config_genesis() -> GenesisConfig {
             authority_keys_from_seed("Alice"),
         ],
         vec![],
-        get_account_id_from_seed::<sr25519::Public>("Alice"),
+        #[cfg(feature = "sudo")] get_account_id_from_seed::<sr25519::Public>("Alice"),
         None,
-        true,
+        #[cfg(feature = "contracts")] true,
+        DEFAULT_NOMINATION_SEED,
     )
 }
 
@@ -370,9 +413,10 @@ fn local_testnet_genesis() -> GenesisConfig {
             authority_keys_from_seed("Bob"),
         ],
         vec![],
-        get_account_id_from_seed::<sr25519::Public>("Alice"),
+        #[cfg(feature = "sudo")] get_account_id_from_seed::<sr25519::Public>("Alice"),
         None,
-        false,
+        #[cfg(feature = "contracts")] false,
+        DEFAULT_NOMINATION_SEED,
     )
 }
 
@@ -404,9 +448,10 @@ pub(crate) mod tests {
                 authority_keys_from_seed("Alice"),
             ],
             vec![],
-            get_account_id_from_seed::<sr25519::Public>("Alice"),
+            #[cfg(feature = "sudo")] get_account_id_from_seed::<sr25519::Public>("Alice"),
             None,
-            false,
+            #[cfg(feature = "contracts")] false,
+            DEFAULT_NOMINATION_SEED,
         )
     }
 
@@ -447,7 +492,7 @@ pub(crate) mod tests {
             integration_test_config_with_two_authorities(),
             |config| {
                 let NewFullBase { task_manager, client, network, transaction_pool, .. }
-                    = new_full_base(config, |_, _| ())?;
+                    = new_full_base(config, Default::default(), |_, _| ())?;
                 Ok(sc_service_test::TestNetComponents::new(task_manager, client, network, transaction_pool))
             },
             |config| {
@@ -457,6 +502,55 @@ pub(crate) mod tests {
         );
     }
 
+    #[test]
+    fn test_nominations_are_deterministic() {
+        let validators = ["Alice", "Bob", "Charlie"];
+        let nominators = ["Dave", "Eve", "Ferdie"];
+        // The nominations of each nominator, as validator names.
+        let nominations = |seed| -> Vec<Vec<&str>> {
+            let stakers = testnet_genesis(
+                validators.iter().map(|name| authority_keys_from_seed(name)).collect(),
+                nominators
+                    .iter()
+                    .map(|name| get_account_id_from_seed::<sr25519::Public>(name))
+                    .collect(),
+                #[cfg(feature = "sudo")] get_account_id_from_seed::<sr25519::Public>("Alice"),
+                None,
+                #[cfg(feature = "contracts")] false,
+                seed,
+            ).pallet_staking.stakers;
+            stakers.into_iter().filter_map(|(_, _, _, status)| match status {
+                StakerStatus::Nominator(targets) => Some(targets.iter().map(|target| {
+                    *validators
+                        .iter()
+                        .find(|name| authority_keys_from_seed(name).0 == *target)
+                        .unwrap()
+                }).collect()),
+                _ => None,
+            }).collect()
+        };
+
+        // Pinned, so that a change of the derivation or of the `rand` crates shows up here.
+        let expected: Vec<Vec<&str>> = vec![vec![], vec!["Charlie", "Alice"], vec!["Bob"]];
+        assert_eq!(nominations(DEFAULT_NOMINATION_SEED), expected);
+        assert_ne!(nominations(42), expected);
+    }
+
+    #[test]
+    fn test_grandpa_parameters_are_read_from_extensions() {
+        let mut spec: serde_json::Value =
+            serde_json::from_str(&development_config().as_json(false).unwrap()).unwrap();
+        spec["grandpaGossipDuration"] = 1000.into();
+        spec["grandpaJustificationPeriod"] = 64.into();
+        let spec = ChainSpec::from_json_bytes(spec.to_string().into_bytes()).unwrap();
+
+        let extensions = Extensions::try_get(&spec).unwrap();
+        assert_eq!(extensions.grandpa_gossip_duration, Some(1000));
+        assert_eq!(extensions.grandpa_justification_period, Some(64));
+        let defaults = development_config();
+        assert_eq!(Extensions::try_get(&defaults).unwrap().grandpa_gossip_duration, None);
+    }
+
     #[test]
     fn test_create_development_chain_spec() {
         development_config().build_storage().unwrap();
@@ -16,7 +16,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use sc_cli::{RunCmd, KeySubcommand, SignCmd, VanityCmd, VerifyCmd};
+use sc_cli::{
+	BlockNumberOrHash, DatabaseParams, KeySubcommand, KeystoreParams, PruningParams, RunCmd,
+	SharedParams, SignCmd, VanityCmd, VerifyCmd,
+};
 use structopt::StructOpt;
 
 /// An overarching CLI command definition.
@@ -28,13 +31,65 @@ pub struct Cli {
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Author blocks with manual-seal instead of BABE and GRANDPA: `instant` on every
+	/// transaction, `manual` only on `engine_createBlock` RPC calls, or `interval=<ms>`.
+	///
+	/// Only available for development chains. `engine_createBlock` works in every mode.
+	#[structopt(long, value_name = "MODE")]
+	pub sealing: Option<Sealing>,
+
+	/// Index the labor events of finalized blocks in a database at the given path, and serve
+	/// them with the `labor_indexedEvents` RPC.
+	///
+	/// Events are read from block state, so indexing an existing chain completely needs
+	/// `--pruning archive`.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub labor_index: Option<std::path::PathBuf>,
+
+	/// POST the labor events of finalized blocks to the HTTP endpoints of the given TOML file.
+	///
+	/// See `res/labor-webhooks.toml` for an example. Delivery cursors are kept in the chain's
+	/// directory of the base path, so no event is lost across restarts.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub labor_webhooks: Option<std::path::PathBuf>,
+}
+
+/// How blocks are authored with `--sealing`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sealing {
+	/// Author a block as soon as a transaction enters the pool.
+	Instant,
+	/// Author blocks on `engine_createBlock` RPC calls only.
+	Manual,
+	/// Author a block every given number of milliseconds, even if it is empty.
+	Interval(u64),
+}
+
+impl std::str::FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"instant" => Ok(Sealing::Instant),
+			"manual" => Ok(Sealing::Manual),
+			_ => s
+				.strip_prefix("interval=")
+				.and_then(|millis| millis.parse().ok())
+				.filter(|millis| *millis > 0)
+				.map(Sealing::Interval)
+				.ok_or_else(|| {
+					format!("Invalid sealing `{}`, expected instant, manual or interval=<ms>", s)
+				}),
+		}
+	}
 }
 
 /// Possible subcommands of the main binary.
 #[derive(Debug, StructOpt)]
 pub enum Subcommand {
 	/// Key management cli utilities
-	Key(KeySubcommand),
+	Key(KeyCmd),
 
 	/// The custom inspect subcommmand for decoding blocks and extrinsics.
 	#[structopt(
@@ -81,4 +136,459 @@ pub enum Subcommand {
 
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
+
+	/// Encrypt and decrypt the details of private labor jobs.
+	JobCrypto(JobCryptoCmd),
+
+	/// Post, inspect and approve labor jobs.
+	Labor(LaborCmd),
+
+	/// Check a proof of job completion from `labor prove` offline.
+	VerifyCompletion(VerifyCompletionCmd),
+
+	/// Build and sign an extrinsic offline.
+	Tx(TxCmd),
+
+	/// Decode a storage item from the local database.
+	Storage(StorageCmd),
+
+	/// Generate a raw chain spec from a TOML or JSON description.
+	GenerateSpec(GenerateSpecCmd),
+
+	/// Derive validator keys from a secret and write a chain spec and their keystores.
+	PrepareTestnet(PrepareTestnetCmd),
+
+	/// Rotate session keys and check their registration.
+	Session(SessionCmd),
+}
+
+/// The `generate-spec` command.
+///
+/// Reads a description of a network with its authorities, endowments, sudo key, token properties
+/// and bootnodes, and writes a raw chain spec for it. See `res/testnet.toml` for an example.
+#[derive(Debug, StructOpt)]
+pub struct GenerateSpecCmd {
+	/// The description of the network. Files ending in `.toml` are read as TOML, all others as
+	/// JSON.
+	#[structopt(parse(from_os_str))]
+	pub input: std::path::PathBuf,
+
+	/// Write the chain spec to this file instead of STDOUT.
+	#[structopt(long, short, parse(from_os_str))]
+	pub output: Option<std::path::PathBuf>,
+
+	/// Launch without a sudo key, leaving root to governance. The description must not have one
+	/// and the node must be built without the `sudo` feature.
+	#[structopt(long)]
+	pub no_sudo: bool,
+}
+
+/// The `key` subcommands of Substrate, and insertion of a validator's session keys.
+#[derive(Debug, StructOpt)]
+pub enum KeyCmd {
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	Substrate(KeySubcommand),
+
+	/// Insert the session keys of a validator from a directory of key files into the keystore.
+	InsertSession(InsertSessionCmd),
+}
+
+/// The `key insert-session` command.
+///
+/// Reads `author_insertKey` JSON-RPC requests, one per file, like those in
+/// `scripts/session_keys`. The files of a validator are named after a key type and the number of
+/// the validator, e.g. `babe1`. Nothing is inserted unless every key of `SessionKeys` is given
+/// exactly once and derives from its secret key URI.
+#[derive(Debug, StructOpt)]
+pub struct InsertSessionCmd {
+	/// The directory holding the key files.
+	#[structopt(long, parse(from_os_str))]
+	pub dir: std::path::PathBuf,
+
+	/// Insert the keys of this validator. Without it, files named only after a key type are read.
+	#[structopt(long)]
+	pub validator: Option<u32>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+/// Session key rotation against the local database.
+///
+/// Rotating generates new keys in the keystore and signs a `Session::set_keys` extrinsic for the
+/// controller, to be submitted with `author_submitExtrinsic`. Once it is in a block, `verify`
+/// confirms that the keys are registered.
+#[derive(Debug, StructOpt)]
+pub enum SessionCmd {
+	/// Generate new session keys and sign the extrinsic registering them.
+	Rotate(SessionRotateCmd),
+
+	/// Check that the session keys registered for a controller's stash are the given keys.
+	Verify(SessionVerifyCmd),
+}
+
+/// The `session rotate` command.
+#[derive(Debug, StructOpt)]
+pub struct SessionRotateCmd {
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub signer: LaborSignerParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub chain: LaborChainParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+/// The `session verify` command.
+#[derive(Debug, StructOpt)]
+pub struct SessionVerifyCmd {
+	/// The SS58 address of the controller.
+	#[structopt(long)]
+	pub controller: String,
+
+	/// The session keys as printed by `session rotate`.
+	#[structopt(long)]
+	pub keys: String,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub chain: LaborChainParams,
+}
+
+/// The `prepare-testnet` command.
+///
+/// Derives the stash, controller and session keys of every validator from
+/// `<secret>//<validator>//<key>`, numbering validators from 1. Writes `chain_spec.json` and,
+/// for every validator, a base path `validator-<n>` whose keystore holds its session keys.
+#[derive(Debug, StructOpt)]
+pub struct PrepareTestnetCmd {
+	/// The number of initial validators.
+	#[structopt(long)]
+	pub validators: u32,
+
+	/// A file holding the secret phrase or seed the keys are derived from.
+	#[structopt(long, parse(from_os_str))]
+	pub secret_file: std::path::PathBuf,
+
+	/// The directory to write the chain spec and the base paths to.
+	#[structopt(long, short, parse(from_os_str), default_value = "testnet")]
+	pub output: std::path::PathBuf,
+
+	/// The name of the network.
+	#[structopt(long, default_value = "Labor Testnet")]
+	pub name: String,
+
+	/// The identifier of the network.
+	#[structopt(long, default_value = "labor_testnet")]
+	pub id: String,
+
+	/// Launch without a sudo key, which needs a node built without the `sudo` feature. Otherwise
+	/// it is derived from `<secret>//sudo`.
+	#[structopt(long)]
+	pub no_sudo: bool,
+}
+
+/// The `storage` command.
+///
+/// Iterates the keys of a storage item in the local database and prints them with their values as
+/// JSON. Keys and values are decoded by the type names in the runtime metadata, which covers the
+/// items of the labor pallet and items of primitive types; other items are rejected. Keys whose
+/// type is not supported are printed as hex.
+#[derive(Debug, StructOpt)]
+pub struct StorageCmd {
+	/// The storage item as `Pallet::Item`, e.g. `LaborModule::Jobs`.
+	pub item: String,
+
+	/// Block hash or number to read at. Defaults to the best block.
+	#[structopt(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// The `tx` command.
+///
+/// Encodes a runtime call given as JSON and signs it with the full `SignedExtra`. The genesis hash
+/// and runtime version are derived from the chain spec, so no database or network is needed.
+#[derive(Debug, StructOpt)]
+pub struct TxCmd {
+	/// The call as JSON, e.g.
+	/// `{"pallet": "Balances", "call": "transfer", "args": {"dest": "5F..", "value": "100"}}`.
+	///
+	/// `args` may also be a positional array. Arguments of types the encoder does not know can be
+	/// passed pre-encoded as `{"scale": "0x.."}`. If not given, the call is read from STDIN.
+	#[structopt(long)]
+	pub call: Option<String>,
+
+	/// The secret key URI of the signer. If not given, you will be prompted for it.
+	#[structopt(long)]
+	pub suri: Option<String>,
+
+	/// The nonce of the signer.
+	#[structopt(long)]
+	pub nonce: u32,
+
+	/// The tip to pay the block author.
+	#[structopt(long, default_value = "0")]
+	pub tip: u128,
+
+	/// Make the extrinsic mortal for this many blocks. The extrinsic is immortal by default.
+	#[structopt(long, requires_all = &["era-block-number", "era-block-hash"])]
+	pub mortality: Option<u64>,
+
+	/// Number of the block the mortal era starts at.
+	#[structopt(long)]
+	pub era_block_number: Option<u64>,
+
+	/// Hash of the block the mortal era starts at.
+	#[structopt(long)]
+	pub era_block_hash: Option<String>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+/// Labor job market commands.
+///
+/// `list`, `show` and `prove` read the local database. `post` and `approve` sign an extrinsic
+/// with the given secret key URI and print it as SCALE hex, ready for `author_submitExtrinsic`;
+/// the genesis hash, runtime version and nonce are taken from the local database.
+#[derive(Debug, StructOpt)]
+pub enum LaborCmd {
+	/// Sign a `post_job` extrinsic.
+	Post(LaborPostCmd),
+
+	/// List the jobs in the local database.
+	List(LaborListCmd),
+
+	/// Show a job and its applicants.
+	Show(LaborShowCmd),
+
+	/// Sign an `approve` extrinsic, releasing the escrow of a job to its worker.
+	Approve(LaborApproveCmd),
+
+	/// Prove that the worker of a completed job completed it, for `verify-completion`.
+	Prove(LaborProveCmd),
+}
+
+/// The database a labor command reads from.
+#[derive(Debug, StructOpt)]
+pub struct LaborChainParams {
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// Signing options of labor commands that build extrinsics.
+#[derive(Debug, StructOpt)]
+pub struct LaborSignerParams {
+	/// The secret key URI of the signer. If not given, you will be prompted for it.
+	#[structopt(long)]
+	pub suri: Option<String>,
+
+	/// The nonce to sign with. Defaults to the signer's nonce at the best block.
+	#[structopt(long)]
+	pub nonce: Option<u32>,
+
+	/// The tip to pay the block author.
+	#[structopt(long, default_value = "0")]
+	pub tip: u128,
+}
+
+/// The `labor post` command.
+#[derive(Debug, StructOpt)]
+pub struct LaborPostCmd {
+	/// The job details.
+	#[structopt(long)]
+	pub details: String,
+
+	/// The payment to escrow, in the smallest unit.
+	#[structopt(long)]
+	pub payment: u128,
+
+	/// The bond required from the assigned worker, in the smallest unit.
+	#[structopt(long, default_value = "0")]
+	pub worker_bond: u128,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub signer: LaborSignerParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub chain: LaborChainParams,
+}
+
+/// The `labor list` command.
+#[derive(Debug, StructOpt)]
+pub struct LaborListCmd {
+	/// Only list jobs that accept applications.
+	#[structopt(long)]
+	pub open: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub chain: LaborChainParams,
+}
+
+/// The `labor show` command.
+#[derive(Debug, StructOpt)]
+pub struct LaborShowCmd {
+	/// The job id.
+	pub id: u32,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub chain: LaborChainParams,
+}
+
+/// The `labor prove` command.
+///
+/// Prints a JSON proof of the completion against the MMR root of a block. The MMR leaves are only
+/// in the database of a node that imported the blocks with `--enable-offchain-indexing true`.
+#[derive(Debug, StructOpt)]
+pub struct LaborProveCmd {
+	/// The job id.
+	pub id: u32,
+
+	/// Block hash or number with the MMR root to prove against. Defaults to the best block.
+	#[structopt(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub chain: LaborChainParams,
+}
+
+/// The `verify-completion` command.
+///
+/// Checks that the MMR leaf of a proof from `labor prove` leads to a trusted MMR root and records
+/// the completion, without a database. Anyone can build a proof that leads to a root of their
+/// own, so the root is required: the `Mmr::RootHash` at the `rootBlockHash` of the proof, on a
+/// node or block explorer that you trust.
+#[derive(Debug, StructOpt)]
+pub struct VerifyCompletionCmd {
+	/// The proof file. If not given, read from STDIN.
+	#[structopt(parse(from_os_str))]
+	pub proof: Option<std::path::PathBuf>,
+
+	/// The trusted MMR root, which the root of the proof must equal.
+	#[structopt(long)]
+	pub root: String,
+}
+
+/// The `labor approve` command.
+#[derive(Debug, StructOpt)]
+pub struct LaborApproveCmd {
+	/// The job id.
+	pub id: u32,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub signer: LaborSignerParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub chain: LaborChainParams,
+}
+
+/// Helpers for private jobs, whose details are stored on-chain only as a payload hash.
+///
+/// The employer encrypts the details under a fresh content key and posts the payload hash. Once
+/// a worker is assigned, the content key is sealed to the worker's sr25519 public key, or to the
+/// x25519 key they published, and published with `share_content_key`.
+#[derive(Debug, StructOpt)]
+pub enum JobCryptoCmd {
+	/// Encrypt job details under a fresh content key.
+	///
+	/// Prints the content key, the encrypted payload and the payload hash to post on-chain.
+	Encrypt {
+		/// The job details. If not given, read from STDIN.
+		#[structopt(long)]
+		message: Option<String>,
+
+		/// The details are hex-encoded.
+		#[structopt(long)]
+		hex: bool,
+	},
+
+	/// Decrypt an encrypted payload with its content key.
+	Decrypt {
+		/// The hex-encoded content key.
+		#[structopt(long)]
+		key: String,
+
+		/// The hex-encoded payload. If not given, read from STDIN.
+		#[structopt(long)]
+		message: Option<String>,
+	},
+
+	/// Seal a content key to a worker's sr25519 or x25519 public key.
+	SealKey {
+		/// The hex-encoded content key.
+		#[structopt(long)]
+		key: String,
+
+		/// The worker's SS58 address or hex-encoded public key.
+		#[structopt(long)]
+		worker: String,
+
+		/// The worker key is an x25519 public key from `x25519-key`.
+		#[structopt(long)]
+		x25519: bool,
+	},
+
+	/// Open a sealed content key with the worker's secret key.
+	OpenKey {
+		/// The hex-encoded sealed key.
+		#[structopt(long)]
+		sealed: String,
+
+		/// The worker's secret key URI. If not given, you will be prompted for it.
+		#[structopt(long)]
+		suri: Option<String>,
+
+		/// The key was sealed to the worker's x25519 key.
+		#[structopt(long)]
+		x25519: bool,
+	},
+
+	/// Print the x25519 public key derived from an account's secret key.
+	///
+	/// Content keys sealed with `seal-key --x25519` to this key are opened with
+	/// `open-key --x25519` and the same secret key URI.
+	X25519Key {
+		/// The secret key URI. If not given, you will be prompted for it.
+		#[structopt(long)]
+		suri: Option<String>,
+	},
 }
@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{chain_spec, service, Cli, Subcommand};
+use crate::{chain_spec, manual_seal, service, Cli, KeyCmd, Subcommand};
 use labor_node_executor::Executor;
 use labor_node_runtime::{Block, RuntimeApi};
 use sc_cli::{Result, SubstrateCli, RuntimeVersion, Role, ChainSpec};
@@ -75,10 +75,16 @@ pub fn run() -> Result<()> {
     match &cli.subcommand {
         None => {
             let runner = cli.create_runner(&cli.run)?;
+            let sealing = cli.sealing;
+            let labor = service::LaborConfig {
+                index_path: cli.labor_index.clone(),
+                webhooks_path: cli.labor_webhooks.clone(),
+            };
             runner.run_node_until_exit(|config| async move {
-                match config.role {
-                    Role::Light => service::new_light(config),
-                    _ => service::new_full(config),
+                match (config.role.clone(), sealing) {
+                    (Role::Light, _) => service::new_light(config),
+                    (_, Some(sealing)) => manual_seal::new_manual_seal(config, sealing, labor),
+                    _ => service::new_full(config, labor),
                 }.map_err(sc_cli::Error::Service)
             })
         }
@@ -97,10 +103,40 @@ pub fn run() -> Result<()> {
 				You can enable it with `--features runtime-benchmarks`.".into())
             }
         }
-        Some(Subcommand::Key(cmd)) => cmd.run(&cli),
+        Some(Subcommand::Key(KeyCmd::Substrate(cmd))) => cmd.run(&cli),
+        Some(Subcommand::Key(KeyCmd::InsertSession(cmd))) => cmd.run(&cli),
         Some(Subcommand::Sign(cmd)) => cmd.run(),
         Some(Subcommand::Verify(cmd)) => cmd.run(),
         Some(Subcommand::Vanity(cmd)) => cmd.run(),
+        Some(Subcommand::JobCrypto(cmd)) => cmd.run(),
+        Some(Subcommand::VerifyCompletion(cmd)) => cmd.run(),
+        Some(Subcommand::GenerateSpec(cmd)) => cmd.run(),
+        Some(Subcommand::PrepareTestnet(cmd)) => cmd.run(),
+        Some(Subcommand::Labor(cmd)) => {
+            let runner = cli.create_runner(cmd)?;
+            runner.sync_run(|config| {
+                let PartialComponents { client, .. } = new_partial(&config)?;
+                cmd.run(client)
+            })
+        }
+        Some(Subcommand::Session(cmd)) => {
+            let runner = cli.create_runner(cmd)?;
+            runner.sync_run(|config| {
+                let PartialComponents { client, .. } = new_partial(&config)?;
+                cmd.run(client)
+            })
+        }
+        Some(Subcommand::Storage(cmd)) => {
+            let runner = cli.create_runner(cmd)?;
+            runner.sync_run(|config| {
+                let PartialComponents { client, .. } = new_partial(&config)?;
+                cmd.run(client)
+            })
+        }
+        Some(Subcommand::Tx(cmd)) => {
+            let runner = cli.create_runner(cmd)?;
+            runner.sync_run(|config| cmd.run(config.chain_spec))
+        }
         Some(Subcommand::BuildSpec(cmd)) => {
             let runner = cli.create_runner(cmd)?;
             runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
@@ -0,0 +1,66 @@
+//! Building and signing extrinsics outside of the runtime.
+
+use codec::Encode;
+use labor_node_runtime::{signed_extra, Call, SignedPayload, UncheckedExtrinsic};
+use node_primitives::{AccountId, Balance, Hash, Index, Signature};
+use sp_core::{crypto::Pair, sr25519};
+use sp_runtime::{
+	generic::Era,
+	traits::{IdentifyAccount, Verify},
+	MultiAddress,
+};
+
+type AccountPublic = <Signature as Verify>::Signer;
+
+/// Chain data that a signed extrinsic commits to.
+pub struct SigningContext {
+	/// Hash of the genesis block.
+	pub genesis_hash: Hash,
+	/// Hash of the block the era starts at; the genesis hash for immortal extrinsics.
+	pub era_block_hash: Hash,
+	/// `spec_version` of the runtime the extrinsic is meant for.
+	pub spec_version: u32,
+	/// `transaction_version` of the runtime the extrinsic is meant for.
+	pub transaction_version: u32,
+}
+
+/// Returns the account of the given signer.
+pub fn account_of(signer: &sr25519::Pair) -> AccountId {
+	AccountPublic::from(signer.public()).into_account()
+}
+
+/// Sign `call` for `signer` with the full `SignedExtra` of the runtime.
+///
+/// The runtime builds the `SignedExtra` itself; only the data that it reads from the state when
+/// signing on-chain comes from `context`.
+pub fn create_extrinsic(
+	signer: &sr25519::Pair,
+	call: Call,
+	nonce: Index,
+	tip: Balance,
+	era: Era,
+	context: &SigningContext,
+) -> UncheckedExtrinsic {
+	let raw_payload = SignedPayload::from_raw(
+		call,
+		signed_extra(nonce, tip, era),
+		(
+			context.spec_version,
+			context.transaction_version,
+			context.genesis_hash,
+			context.era_block_hash,
+			(),
+			(),
+			(),
+		),
+	);
+	let signature = raw_payload.using_encoded(|payload| signer.sign(payload));
+	let (call, extra, _) = raw_payload.deconstruct();
+
+	UncheckedExtrinsic::new_signed(
+		call,
+		MultiAddress::Id(account_of(signer)),
+		signature.into(),
+		extra,
+	)
+}
@@ -0,0 +1,321 @@
+//! The `generate-spec` command: chain specs from a declarative description of the network.
+
+use crate::{
+	chain_spec::{testnet_genesis, ChainSpec, GenesisConfig},
+	GenerateSpecCmd,
+};
+use grandpa_primitives::AuthorityId as GrandpaId;
+use node_primitives::{AccountId, Balance};
+use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
+use sc_cli::Error;
+use sc_network::config::MultiaddrWithPeerId;
+use sc_service::ChainType;
+use sc_telemetry::TelemetryEndpoints;
+use serde::Deserialize;
+use serde_json::map::Map;
+use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
+use sp_consensus_babe::AuthorityId as BabeId;
+use sp_core::{
+	bytes::from_hex,
+	crypto::{Pair, Public, Ss58Codec, UncheckedFrom},
+	sr25519,
+};
+use sp_runtime::{traits::IdentifyAccount, MultiSigner};
+use std::convert::TryFrom;
+
+/// The description of a network, as read from TOML or JSON.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Description {
+	name: String,
+	id: String,
+	#[serde(default = "live")]
+	chain_type: ChainType,
+	#[serde(default)]
+	protocol_id: Option<String>,
+	#[serde(default)]
+	boot_nodes: Vec<MultiaddrWithPeerId>,
+	#[serde(default)]
+	telemetry_endpoints: Vec<String>,
+	#[serde(default)]
+	properties: Option<Map<String, serde_json::Value>>,
+	/// The sudo key as SS58 address, hex public key or secret key URI. Required unless the
+	/// network launches with `--no-sudo`, on a node built without the `sudo` feature.
+	#[serde(default)]
+	sudo: Option<String>,
+	authorities: Vec<Authority>,
+	#[serde(default)]
+	nominators: Vec<String>,
+	#[serde(default)]
+	endowments: Vec<Endowment>,
+	/// Seed of the nominations of the initial nominators.
+	#[serde(default)]
+	nomination_seed: u64,
+	/// Slot duration in milliseconds, the runtime default if not given.
+	#[serde(default)]
+	slot_duration: Option<u64>,
+	/// Epoch duration in slots, the runtime default if not given.
+	#[serde(default)]
+	epoch_duration: Option<u64>,
+	/// Probability of a slot being a primary BABE slot as `[numerator, denominator]`.
+	#[serde(default)]
+	primary_probability: Option<(u64, u64)>,
+}
+
+/// An initial authority, either derived from a secret key URI like the development authorities,
+/// or given by its accounts and public session keys.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Authority {
+	suri: Option<String>,
+	stash: Option<String>,
+	controller: Option<String>,
+	grandpa: Option<String>,
+	babe: Option<String>,
+	im_online: Option<String>,
+	authority_discovery: Option<String>,
+}
+
+/// An endowed account. Without a balance it receives the default testnet endowment.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Endowment {
+	account: String,
+	balance: Option<Amount>,
+}
+
+/// Balances may be given as numbers or, for values beyond `u64`, as strings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Amount {
+	Number(u64),
+	Text(String),
+}
+
+type AuthorityKeys = (AccountId, AccountId, GrandpaId, BabeId, ImOnlineId, AuthorityDiscoveryId);
+
+/// The description with all keys resolved, from which the genesis config is built.
+#[derive(Clone)]
+struct Genesis {
+	authorities: Vec<AuthorityKeys>,
+	nominators: Vec<AccountId>,
+	sudo: Option<AccountId>,
+	endowed: Vec<AccountId>,
+	balances: Vec<(AccountId, Balance)>,
+	nomination_seed: u64,
+	slot_duration: Option<u64>,
+	epoch_duration: Option<u64>,
+	primary_probability: Option<(u64, u64)>,
+}
+
+impl GenerateSpecCmd {
+	/// Run the command.
+	pub fn run(&self) -> Result<(), Error> {
+		let input = std::fs::read(&self.input)?;
+		let description: Description = if self.input.extension().map_or(false, |e| e == "toml") {
+			toml::from_slice(&input).map_err(|e| Error::Input(e.to_string()))?
+		} else {
+			serde_json::from_slice(&input).map_err(|e| Error::Input(e.to_string()))?
+		};
+
+		let spec = chain_spec(description, self.no_sudo).map_err(Error::Input)?;
+		let json = sc_service::ChainSpec::as_json(&spec, true).map_err(Error::Input)?;
+		match &self.output {
+			Some(path) => std::fs::write(path, json)?,
+			None => println!("{}", json),
+		}
+		Ok(())
+	}
+}
+
+fn live() -> ChainType {
+	ChainType::Live
+}
+
+fn chain_spec(description: Description, no_sudo: bool) -> Result<ChainSpec, String> {
+	if description.authorities.is_empty() {
+		return Err("At least one authority is required".into())
+	}
+	let sudo = match (&description.sudo, no_sudo) {
+		(Some(sudo), false) => Some(account(sudo)?),
+		(None, true) => None,
+		(Some(_), true) => return Err("A sudo key is given despite `--no-sudo`".into()),
+		(None, false) => return Err("A sudo key is required unless `--no-sudo` is given".into()),
+	};
+	let genesis = Genesis {
+		authorities: description
+			.authorities
+			.iter()
+			.map(authority_keys)
+			.collect::<Result<_, _>>()?,
+		nominators: description
+			.nominators
+			.iter()
+			.map(|nominator| account(nominator))
+			.collect::<Result<_, _>>()?,
+		sudo,
+		endowed: description
+			.endowments
+			.iter()
+			.map(|endowment| account(&endowment.account))
+			.collect::<Result<_, _>>()?,
+		balances: description
+			.endowments
+			.iter()
+			.filter_map(|endowment| {
+				let balance = endowment.balance.as_ref()?;
+				Some(account(&endowment.account).and_then(|a| Ok((a, amount(balance)?))))
+			})
+			.collect::<Result<_, _>>()?,
+		nomination_seed: description.nomination_seed,
+		slot_duration: description.slot_duration,
+		epoch_duration: description.epoch_duration,
+		primary_probability: description.primary_probability,
+	};
+	check_sudo(&genesis.sudo)?;
+	if genesis.slot_duration.map_or(false, |slot| slot == 0 || slot % 2 != 0) {
+		return Err("The slot duration must be even and not zero".into())
+	}
+	if genesis.epoch_duration == Some(0) {
+		return Err("The epoch duration must not be zero".into())
+	}
+	if genesis.primary_probability.map_or(false, |(n, d)| d == 0 || n > d) {
+		return Err("The primary probability must be a fraction of at most one".into())
+	}
+
+	let telemetry = if description.telemetry_endpoints.is_empty() {
+		None
+	} else {
+		let endpoints = description.telemetry_endpoints.into_iter().map(|url| (url, 0)).collect();
+		Some(TelemetryEndpoints::new(endpoints).map_err(|e| e.to_string())?)
+	};
+
+	Ok(ChainSpec::from_genesis(
+		&description.name,
+		&description.id,
+		description.chain_type,
+		move || genesis.build(),
+		description.boot_nodes,
+		telemetry,
+		description.protocol_id.as_deref(),
+		description.properties,
+		Default::default(),
+	))
+}
+
+impl Genesis {
+	fn build(&self) -> GenesisConfig {
+		let mut genesis = testnet_genesis(
+			self.authorities.clone(),
+			self.nominators.clone(),
+			#[cfg(feature = "sudo")] self.sudo.clone().expect("checked by `check_sudo`; qed"),
+			Some(self.endowed.clone()),
+			#[cfg(feature = "contracts")] false,
+			self.nomination_seed,
+		);
+		for (who, balance) in &self.balances {
+			genesis
+				.pallet_balances
+				.balances
+				.iter_mut()
+				.filter(|(account, _)| account == who)
+				.for_each(|(_, endowment)| *endowment = *balance);
+		}
+		genesis.pallet_timing.slot_duration = self.slot_duration.unwrap_or_default();
+		genesis.pallet_timing.epoch_duration = self.epoch_duration.unwrap_or_default();
+		if let (Some(c), Some(epoch_config)) =
+			(self.primary_probability, genesis.pallet_babe.epoch_config.as_mut())
+		{
+			epoch_config.c = c;
+		}
+		genesis
+	}
+}
+
+/// Checks that there is a sudo key if and only if the runtime has the sudo pallet. Its genesis
+/// always sets a key, so a network without one needs a node built without the `sudo` feature.
+#[cfg(feature = "sudo")]
+pub(crate) fn check_sudo(sudo: &Option<AccountId>) -> Result<(), String> {
+	match sudo {
+		Some(_) => Ok(()),
+		None => Err("`--no-sudo` needs a node built without the `sudo` feature".into()),
+	}
+}
+
+/// Checks that there is a sudo key if and only if the runtime has the sudo pallet.
+#[cfg(not(feature = "sudo"))]
+pub(crate) fn check_sudo(sudo: &Option<AccountId>) -> Result<(), String> {
+	match sudo {
+		Some(_) => Err("The runtime of this node has no sudo pallet, pass `--no-sudo`".into()),
+		None => Ok(()),
+	}
+}
+
+fn authority_keys(authority: &Authority) -> Result<AuthorityKeys, String> {
+	if let Some(suri) = &authority.suri {
+		return Ok((
+			account_from_suri(&format!("{}//stash", suri))?,
+			account_from_suri(suri)?,
+			public_from_suri::<GrandpaId>(suri)?,
+			public_from_suri::<BabeId>(suri)?,
+			public_from_suri::<ImOnlineId>(suri)?,
+			public_from_suri::<AuthorityDiscoveryId>(suri)?,
+		))
+	}
+
+	fn required<'a>(key: &'a Option<String>, name: &str) -> Result<&'a str, String> {
+		key.as_deref()
+			.ok_or_else(|| format!("An authority without `suri` needs a `{}` key", name))
+	}
+	Ok((
+		account(required(&authority.stash, "stash")?)?,
+		account(required(&authority.controller, "controller")?)?,
+		public(required(&authority.grandpa, "grandpa")?)?,
+		public(required(&authority.babe, "babe")?)?,
+		public(required(&authority.im_online, "imOnline")?)?,
+		public(required(&authority.authority_discovery, "authorityDiscovery")?)?,
+	))
+}
+
+/// An account given as SS58 address, hex public key or secret key URI.
+fn account(value: &str) -> Result<AccountId, String> {
+	if value.starts_with("0x") {
+		Ok(hex_public(value)?.into())
+	} else if let Ok(account) = AccountId::from_ss58check(value) {
+		Ok(account)
+	} else {
+		account_from_suri(value)
+	}
+}
+
+pub(crate) fn account_from_suri(suri: &str) -> Result<AccountId, String> {
+	let public = public_from_suri::<sr25519::Public>(suri)?;
+	Ok(MultiSigner::from(public).into_account())
+}
+
+fn public_from_suri<TPublic: Public>(suri: &str) -> Result<<TPublic::Pair as Pair>::Public, String> {
+	TPublic::Pair::from_string(suri, None)
+		.map(|pair| pair.public())
+		.map_err(|_| format!("Invalid secret key URI `{}`", suri))
+}
+
+/// A public session key given as SS58 address or hex.
+fn public<T: Ss58Codec + UncheckedFrom<[u8; 32]>>(value: &str) -> Result<T, String> {
+	if value.starts_with("0x") {
+		Ok(T::unchecked_from(hex_public(value)?))
+	} else {
+		T::from_ss58check(value).map_err(|_| format!("Invalid public key `{}`", value))
+	}
+}
+
+fn hex_public(value: &str) -> Result<[u8; 32], String> {
+	let bytes = from_hex(value).map_err(|_| format!("Invalid hex `{}`", value))?;
+	<[u8; 32]>::try_from(bytes.as_slice()).map_err(|_| format!("Expected 32 bytes: `{}`", value))
+}
+
+fn amount(amount: &Amount) -> Result<Balance, String> {
+	match amount {
+		Amount::Number(n) => Ok((*n).into()),
+		Amount::Text(s) => s.parse().map_err(|_| format!("Invalid balance `{}`", s)),
+	}
+}
@@ -0,0 +1,263 @@
+//! Encryption of private job details.
+//!
+//! Job details are encrypted with ChaCha20-Poly1305 under a random content key; the payload is
+//! `nonce ++ ciphertext` and its blake2-256 hash is what goes on-chain. Content keys are sealed
+//! to a worker by an ephemeral Diffie-Hellman exchange, either on the Ristretto group that sr25519
+//! keys live in, so a worker opens them with nothing but their account's secret key, or by x25519
+//! to a key the worker published. A worker's x25519 key is derived from their sr25519 secret key,
+//! so it needs no separate backup.
+
+use crate::JobCryptoCmd;
+use chacha20poly1305::{
+	aead::{Aead, NewAead},
+	ChaCha20Poly1305, Key, Nonce,
+};
+use curve25519_dalek::{
+	constants::{RISTRETTO_BASEPOINT_TABLE, X25519_BASEPOINT},
+	montgomery::MontgomeryPoint,
+	ristretto::{CompressedRistretto, RistrettoPoint},
+	scalar::Scalar,
+};
+use rand::{rngs::OsRng, RngCore};
+use sc_cli::{utils, Error};
+use sp_core::{
+	bytes::from_hex,
+	crypto::{Pair as _, Ss58Codec},
+	hashing::blake2_256,
+	hexdisplay::HexDisplay,
+	sr25519,
+};
+
+const NONCE_LEN: usize = 12;
+const SEAL_CONTEXT: &[u8] = b"labor-node/sealed-key";
+const X25519_SEAL_CONTEXT: &[u8] = b"labor-node/sealed-key/x25519";
+const X25519_KEY_CONTEXT: &[u8] = b"labor-node/x25519-key";
+
+impl JobCryptoCmd {
+	/// Run the command.
+	pub fn run(&self) -> Result<(), Error> {
+		match self {
+			JobCryptoCmd::Encrypt { message, hex } => {
+				let details = utils::read_message(message.as_ref(), *hex)?;
+				let mut key = [0u8; 32];
+				OsRng.fill_bytes(&mut key);
+				let payload = encrypt_payload(&key, &details)?;
+
+				println!("Content key:  0x{}", HexDisplay::from(&key));
+				println!("Payload hash: 0x{}", HexDisplay::from(&blake2_256(&payload)));
+				println!("Payload:      0x{}", HexDisplay::from(&payload));
+			},
+			JobCryptoCmd::Decrypt { key, message } => {
+				let key = decode_key(key)?;
+				let payload = utils::read_message(message.as_ref(), true)?;
+				let details = decrypt_payload(&key, &payload)?;
+
+				match String::from_utf8(details) {
+					Ok(text) => println!("{}", text),
+					Err(e) => println!("0x{}", HexDisplay::from(e.as_bytes())),
+				}
+			},
+			JobCryptoCmd::SealKey { key, worker, x25519 } => {
+				let key = decode_key(key)?;
+				let sealed = if *x25519 {
+					seal_content_key_x25519(&decode_x25519(worker)?, &key)?
+				} else {
+					seal_content_key(&decode_public(worker)?, &key)?
+				};
+
+				println!("0x{}", HexDisplay::from(&sealed));
+			},
+			JobCryptoCmd::OpenKey { sealed, suri, x25519 } => {
+				let sealed = decode_hex(sealed)?;
+				let pair = read_pair(suri.as_ref())?;
+				let key = if *x25519 {
+					open_content_key_x25519(&x25519_secret(&pair), &sealed)?
+				} else {
+					open_content_key(&pair, &sealed)?
+				};
+
+				println!("0x{}", HexDisplay::from(&key));
+			},
+			JobCryptoCmd::X25519Key { suri } => {
+				let pair = read_pair(suri.as_ref())?;
+
+				println!("0x{}", HexDisplay::from(&x25519_public(&x25519_secret(&pair))));
+			},
+		}
+		Ok(())
+	}
+}
+
+/// Encrypt `details` under `key`, returning `nonce ++ ciphertext`.
+pub fn encrypt_payload(key: &[u8; 32], details: &[u8]) -> Result<Vec<u8>, Error> {
+	let mut nonce = [0u8; NONCE_LEN];
+	OsRng.fill_bytes(&mut nonce);
+
+	let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key))
+		.encrypt(Nonce::from_slice(&nonce), details)
+		.map_err(|_| Error::Input("Encryption failed".into()))?;
+
+	Ok(nonce.iter().copied().chain(ciphertext).collect())
+}
+
+/// Decrypt a payload produced by [`encrypt_payload`].
+pub fn decrypt_payload(key: &[u8; 32], payload: &[u8]) -> Result<Vec<u8>, Error> {
+	if payload.len() < NONCE_LEN {
+		return Err(Error::Input("Payload too short".into()))
+	}
+	let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+
+	ChaCha20Poly1305::new(Key::from_slice(key))
+		.decrypt(Nonce::from_slice(nonce), ciphertext)
+		.map_err(|_| Error::Input("Wrong content key or corrupted payload".into()))
+}
+
+/// Seal `key` to `worker`, returning `ephemeral public ++ nonce ++ ciphertext`.
+pub fn seal_content_key(worker: &sr25519::Public, key: &[u8; 32]) -> Result<Vec<u8>, Error> {
+	let worker_point = decode_point(worker.as_ref())?;
+	let ephemeral = Scalar::random(&mut OsRng);
+	let ephemeral_public = (&ephemeral * &RISTRETTO_BASEPOINT_TABLE).compress();
+
+	let shared_point = (ephemeral * worker_point).compress();
+	let shared =
+		shared_key(SEAL_CONTEXT, shared_point.as_bytes(), ephemeral_public.as_bytes(), worker);
+	let sealed = encrypt_payload(&shared, key)?;
+
+	Ok(ephemeral_public.as_bytes().iter().copied().chain(sealed).collect())
+}
+
+/// Open a content key sealed by [`seal_content_key`] with the worker's key pair.
+pub fn open_content_key(pair: &sr25519::Pair, sealed: &[u8]) -> Result<Vec<u8>, Error> {
+	if sealed.len() < 32 {
+		return Err(Error::Input("Sealed key too short".into()))
+	}
+	let (ephemeral_public, sealed) = sealed.split_at(32);
+
+	// The first half of an sr25519 secret key is its scalar; it is not always reduced.
+	let mut secret = [0u8; 32];
+	secret.copy_from_slice(&pair.to_raw_vec()[..32]);
+	let secret = Scalar::from_bytes_mod_order(secret);
+
+	let shared_point = (secret * decode_point(ephemeral_public)?).compress();
+	let shared =
+		shared_key(SEAL_CONTEXT, shared_point.as_bytes(), ephemeral_public, &pair.public());
+	decrypt_payload(&shared, sealed)
+}
+
+/// The x25519 secret key of an account, derived from the scalar of its sr25519 secret key.
+pub fn x25519_secret(pair: &sr25519::Pair) -> [u8; 32] {
+	let mut input = X25519_KEY_CONTEXT.to_vec();
+	input.extend_from_slice(&pair.to_raw_vec()[..32]);
+	blake2_256(&input)
+}
+
+/// The x25519 public key of `secret`.
+pub fn x25519_public(secret: &[u8; 32]) -> [u8; 32] {
+	x25519(secret, &X25519_BASEPOINT).to_bytes()
+}
+
+/// Seal `key` to the x25519 public key `worker`, returning `ephemeral public ++ nonce ++
+/// ciphertext`.
+pub fn seal_content_key_x25519(worker: &[u8; 32], key: &[u8; 32]) -> Result<Vec<u8>, Error> {
+	let mut ephemeral = [0u8; 32];
+	OsRng.fill_bytes(&mut ephemeral);
+	let ephemeral_public = x25519_public(&ephemeral);
+
+	let shared_point = x25519(&ephemeral, &MontgomeryPoint(*worker));
+	if shared_point.to_bytes() == [0u8; 32] {
+		return Err(Error::Input("Invalid x25519 public key".into()))
+	}
+	let shared =
+		shared_key(X25519_SEAL_CONTEXT, shared_point.as_bytes(), &ephemeral_public, worker);
+	let sealed = encrypt_payload(&shared, key)?;
+
+	Ok(ephemeral_public.iter().copied().chain(sealed).collect())
+}
+
+/// Open a content key sealed by [`seal_content_key_x25519`] with the worker's x25519 secret key.
+pub fn open_content_key_x25519(secret: &[u8; 32], sealed: &[u8]) -> Result<Vec<u8>, Error> {
+	if sealed.len() < 32 {
+		return Err(Error::Input("Sealed key too short".into()))
+	}
+	let (ephemeral_public, sealed) = sealed.split_at(32);
+	let mut point = [0u8; 32];
+	point.copy_from_slice(ephemeral_public);
+
+	let shared_point = x25519(secret, &MontgomeryPoint(point));
+	let worker = x25519_public(secret);
+	let shared = shared_key(X25519_SEAL_CONTEXT, shared_point.as_bytes(), ephemeral_public, worker);
+	decrypt_payload(&shared, sealed)
+}
+
+/// The x25519 function of RFC 7748, clamping `secret` before the multiplication.
+fn x25519(secret: &[u8; 32], point: &MontgomeryPoint) -> MontgomeryPoint {
+	let mut scalar = *secret;
+	scalar[0] &= 248;
+	scalar[31] &= 127;
+	scalar[31] |= 64;
+	Scalar::from_bits(scalar) * point
+}
+
+fn shared_key(
+	context: &[u8],
+	shared: &[u8],
+	ephemeral_public: &[u8],
+	worker: impl AsRef<[u8]>,
+) -> [u8; 32] {
+	let mut input = context.to_vec();
+	input.extend_from_slice(shared);
+	input.extend_from_slice(ephemeral_public);
+	input.extend_from_slice(worker.as_ref());
+	blake2_256(&input)
+}
+
+fn decode_point(bytes: &[u8]) -> Result<RistrettoPoint, Error> {
+	CompressedRistretto::from_slice(bytes)
+		.decompress()
+		.ok_or_else(|| Error::Input("Invalid sr25519 public key".into()))
+}
+
+fn decode_public(worker: &str) -> Result<sr25519::Public, Error> {
+	if worker.starts_with("0x") {
+		let bytes = decode_hex(worker)?;
+		if bytes.len() != 32 {
+			return Err(Error::Input("Public key must be 32 bytes".into()))
+		}
+		let mut public = [0u8; 32];
+		public.copy_from_slice(&bytes);
+		Ok(sr25519::Public::from_raw(public))
+	} else {
+		sr25519::Public::from_ss58check(worker)
+			.map_err(|_| Error::Input("Invalid SS58 address".into()))
+	}
+}
+
+fn decode_x25519(worker: &str) -> Result<[u8; 32], Error> {
+	let bytes = decode_hex(worker)?;
+	if bytes.len() != 32 {
+		return Err(Error::Input("x25519 public key must be 32 bytes".into()))
+	}
+	let mut public = [0u8; 32];
+	public.copy_from_slice(&bytes);
+	Ok(public)
+}
+
+fn read_pair(suri: Option<&String>) -> Result<sr25519::Pair, Error> {
+	let suri = utils::read_uri(suri)?;
+	sr25519::Pair::from_string(&suri, None)
+		.map_err(|_| Error::Input("Invalid secret key URI".into()))
+}
+
+fn decode_key(key: &str) -> Result<[u8; 32], Error> {
+	let bytes = decode_hex(key)?;
+	if bytes.len() != 32 {
+		return Err(Error::Input("Content key must be 32 bytes".into()))
+	}
+	let mut key = [0u8; 32];
+	key.copy_from_slice(&bytes);
+	Ok(key)
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, Error> {
+	from_hex(value).map_err(|_| Error::Input("Invalid hex".into()))
+}
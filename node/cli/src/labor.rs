@@ -0,0 +1,210 @@
+//! The `labor` subcommand group.
+
+use crate::{
+	extrinsic::{account_of, create_extrinsic, SigningContext},
+	labor_proof::{prove, verify, CompletionProof},
+	labor_rpc::job_value,
+	labor_storage::{self, Job, BLAKE2_128_LEN},
+	service::FullClient,
+	LaborChainParams, LaborCmd, LaborSignerParams, VerifyCompletionCmd,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	storage::StoragePrefixedMap,
+	Blake2_128Concat, StorageHasher,
+};
+use frame_system_rpc_runtime_api::AccountNonceApi;
+use labor_node_runtime::{Call, Runtime, UncheckedExtrinsic};
+use node_primitives::{AccountId, Block, Hash};
+use pallet_labor::{Applications, JobId, JobStatus};
+use sc_cli::{utils, CliConfiguration, DatabaseParams, Error, PruningParams, SharedParams};
+use sc_client_api::StorageProvider;
+use serde_json::json;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	crypto::{Pair, Ss58Codec},
+	hexdisplay::HexDisplay,
+	sr25519,
+	storage::StorageKey,
+};
+use sp_runtime::generic::{BlockId, Era};
+use std::io::Read;
+
+impl LaborCmd {
+	/// Run the command against the given client.
+	pub fn run(&self, client: std::sync::Arc<FullClient>) -> Result<(), Error> {
+		match self {
+			LaborCmd::Post(cmd) => {
+				let call = pallet_labor::Call::<Runtime>::post_job(
+					cmd.details.as_bytes().to_vec(),
+					cmd.payment,
+					cmd.worker_bond,
+				);
+				sign(&client, &cmd.signer, call.into())
+			},
+			LaborCmd::Approve(cmd) => {
+				sign(&client, &cmd.signer, pallet_labor::Call::<Runtime>::approve(cmd.id).into())
+			},
+			LaborCmd::List(cmd) => {
+				let jobs = jobs(&client)?
+					.into_iter()
+					.filter(|(_, job)| !cmd.open || job.status == JobStatus::Open)
+					.map(|(id, job)| job_json(id, &job))
+					.collect::<Vec<_>>();
+				print_json(&json!(jobs))
+			},
+			LaborCmd::Show(cmd) => {
+				let at = BlockId::Hash(client.info().best_hash);
+				let job = labor_storage::read_job(&client, &at, cmd.id)
+					.map_err(Error::Input)?
+					.ok_or_else(|| Error::Input(format!("Job {} not found", cmd.id)))?;
+
+				let mut value = job_json(cmd.id, &job);
+				value["applicants"] = json!(applicants(&client, cmd.id)?);
+				print_json(&value)
+			},
+			LaborCmd::Prove(cmd) => {
+				let at = match &cmd.at {
+					Some(at) => at.parse::<Block>().map_err(Error::Input)?,
+					None => BlockId::Hash(client.info().best_hash),
+				};
+				let at = client.expect_block_hash_from_id(&at)?;
+				let proof = prove(&client, at, cmd.id).map_err(Error::Input)?;
+				print_json(&json!(proof))
+			},
+		}
+	}
+
+	fn chain(&self) -> &LaborChainParams {
+		match self {
+			LaborCmd::Post(cmd) => &cmd.chain,
+			LaborCmd::List(cmd) => &cmd.chain,
+			LaborCmd::Show(cmd) => &cmd.chain,
+			LaborCmd::Approve(cmd) => &cmd.chain,
+			LaborCmd::Prove(cmd) => &cmd.chain,
+		}
+	}
+}
+
+impl VerifyCompletionCmd {
+	/// Run the command.
+	pub fn run(&self) -> Result<(), Error> {
+		let json = match &self.proof {
+			Some(path) => std::fs::read(path)?,
+			None => {
+				let mut json = Vec::new();
+				std::io::stdin().read_to_end(&mut json)?;
+				json
+			},
+		};
+		let proof: CompletionProof = serde_json::from_slice(&json)
+			.map_err(|e| Error::Input(format!("Invalid proof: {}", e)))?;
+		let root = self
+			.root
+			.trim_start_matches("0x")
+			.parse::<Hash>()
+			.map_err(|e| Error::Input(format!("Invalid root {}: {:?}", self.root, e)))?;
+		verify(&proof, root).map_err(Error::Input)?;
+		println!(
+			"{} completed job {} for a payment of {} in block {:?}",
+			proof.worker, proof.job, proof.payment, proof.block_hash,
+		);
+		println!(
+			"MMR root {:?} at block #{} ({:?})",
+			proof.root, proof.root_block, proof.root_block_hash,
+		);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for LaborCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.chain().shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.chain().pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.chain().database_params)
+	}
+}
+
+/// Sign `call` as an immortal extrinsic for the best block of the local database and print it.
+fn sign(client: &FullClient, params: &LaborSignerParams, call: Call) -> Result<(), Error> {
+	let extrinsic = signed_extrinsic(client, params, call)?;
+	println!("0x{}", HexDisplay::from(&extrinsic.encode()));
+	Ok(())
+}
+
+/// Sign `call` as an immortal extrinsic for the best block of the local database.
+pub(crate) fn signed_extrinsic(
+	client: &FullClient,
+	params: &LaborSignerParams,
+	call: Call,
+) -> Result<UncheckedExtrinsic, Error> {
+	let suri = utils::read_uri(params.suri.as_ref())?;
+	let signer = sr25519::Pair::from_string(&suri, None)
+		.map_err(|_| Error::Input("Invalid secret key URI".into()))?;
+
+	let info = client.info();
+	let at = BlockId::Hash(info.best_hash);
+	let nonce = match params.nonce {
+		Some(nonce) => nonce,
+		None => client
+			.runtime_api()
+			.account_nonce(&at, account_of(&signer))
+			.map_err(|e| Error::Input(format!("Failed to read the nonce: {:?}", e)))?,
+	};
+	let version = client.runtime_version_at(&at)?;
+	let context = SigningContext {
+		genesis_hash: info.genesis_hash,
+		era_block_hash: info.genesis_hash,
+		spec_version: version.spec_version,
+		transaction_version: version.transaction_version,
+	};
+
+	Ok(create_extrinsic(&signer, call, nonce, params.tip, Era::Immortal, &context))
+}
+
+/// All jobs at the best block, ordered by id.
+fn jobs(client: &FullClient) -> Result<Vec<(JobId, Job)>, Error> {
+	labor_storage::jobs(client, &BlockId::Hash(client.info().best_hash)).map_err(Error::Input)
+}
+
+/// Applicants of a job at the best block, with the accounts that referred them.
+fn applicants(client: &FullClient, id: JobId) -> Result<Vec<serde_json::Value>, Error> {
+	let at = BlockId::Hash(client.info().best_hash);
+	let mut prefix = Applications::<Runtime>::final_prefix().to_vec();
+	prefix.extend(Blake2_128Concat::hash(&id.encode()));
+
+	client
+		.storage_pairs(&at, &StorageKey(prefix.clone()))?
+		.into_iter()
+		.map(|(key, value)| {
+			let worker = AccountId::decode(&mut &key.0[prefix.len() + BLAKE2_128_LEN..])?;
+			let referrer = Option::<AccountId>::decode(&mut &value.0[..])?;
+			Ok(json!({
+				"worker": worker.to_ss58check(),
+				"referrer": referrer.map(|r| r.to_ss58check()),
+			}))
+		})
+		.collect::<Result<Vec<_>, codec::Error>>()
+		.map_err(Into::into)
+}
+
+/// A job as JSON, with accounts in SS58 format.
+pub(crate) fn job_json(id: JobId, job: &Job) -> serde_json::Value {
+	let mut value = job_value(job);
+	value["id"] = json!(id);
+	value
+}
+
+pub(crate) fn print_json(value: &serde_json::Value) -> Result<(), Error> {
+	let json = serde_json::to_string_pretty(value)
+		.map_err(|e| Error::Input(format!("Failed to print JSON: {}", e)))?;
+	println!("{}", json);
+	Ok(())
+}
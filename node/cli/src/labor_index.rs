@@ -0,0 +1,449 @@
+//! The `--labor-index` service: a local index of the `pallet_labor` events of finalized blocks,
+//! queried by account, job and time range with the `labor_indexedEvents` RPC.
+//!
+//! Every indexed event is stored once, as JSON keyed by block time, block number and event
+//! index, so that a range of keys is a time range. The `accounts` and `jobs` trees map an
+//! account or job followed by such a key to the event.
+
+use crate::{labor_storage::events, service::FullClient};
+use codec::{Decode, Encode};
+use futures::StreamExt;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as RpcResult};
+use jsonrpc_derive::rpc;
+use labor_node_runtime::{Event, Runtime};
+use node_primitives::{AccountId, BlockNumber, Hash, Moment};
+use pallet_labor::{Event as LaborEvent, JobId};
+use sc_client_api::{BlockchainEvents, StorageProvider};
+use sc_service::error::Error as ServiceError;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, storage::StorageKey};
+use sp_runtime::generic::BlockId;
+use std::{path::Path, sync::Arc};
+
+const LOG_TARGET: &str = "labor-index";
+
+/// Key of the number of the next block to index.
+const NEXT_BLOCK: &[u8] = b"next_block";
+
+/// The number of events returned when the query sets no limit.
+const DEFAULT_LIMIT: u32 = 100;
+
+/// The most events returned by one query.
+const MAX_LIMIT: u32 = 1000;
+
+/// A `pallet_labor` event of a finalized block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedEvent {
+	/// The block number.
+	pub block: BlockNumber,
+	/// The block hash.
+	pub hash: Hash,
+	/// The timestamp of the block, in milliseconds since the Unix epoch.
+	pub time: Moment,
+	/// The index of the event in the block.
+	pub index: u32,
+	/// The name of the event, e.g. `JobPosted`.
+	pub event: String,
+	/// The job the event is about, if any.
+	pub job: Option<JobId>,
+	/// The accounts the event names, in SS58 format.
+	pub accounts: Vec<String>,
+	/// The remaining fields of the event. Balances are strings.
+	pub data: serde_json::Value,
+}
+
+/// The filter of a `labor_indexedEvents` query. Every field is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EventQuery {
+	/// Only events naming this account, in SS58 format.
+	pub account: Option<String>,
+	/// Only events about this job.
+	pub job: Option<JobId>,
+	/// Only events of blocks from this time on, in milliseconds since the Unix epoch.
+	pub from: Option<Moment>,
+	/// Only events of blocks before this time, in milliseconds since the Unix epoch.
+	pub to: Option<Moment>,
+	/// The most events to return, 100 by default, at least 1 and at most 1000.
+	pub limit: Option<u32>,
+}
+
+/// Labor event index RPC methods.
+#[rpc]
+pub trait LaborIndexApi {
+	/// The indexed events that match the query, oldest first.
+	#[rpc(name = "labor_indexedEvents")]
+	fn indexed_events(&self, query: EventQuery) -> RpcResult<Vec<IndexedEvent>>;
+}
+
+/// The event index, stored with sled.
+#[derive(Clone)]
+pub struct LaborIndex {
+	db: sled::Db,
+	events: sled::Tree,
+	accounts: sled::Tree,
+	jobs: sled::Tree,
+}
+
+impl LaborIndex {
+	/// Opens the index at `path`, creating it if it does not exist.
+	pub fn open(path: &Path) -> Result<Self, ServiceError> {
+		let open = || -> Result<Self, sled::Error> {
+			let db = sled::open(path)?;
+			Ok(Self {
+				events: db.open_tree("events")?,
+				accounts: db.open_tree("accounts")?,
+				jobs: db.open_tree("jobs")?,
+				db,
+			})
+		};
+		open().map_err(|e| {
+			let path = path.display();
+			ServiceError::Other(format!("Failed to open the labor index at {}: {}", path, e))
+		})
+	}
+
+	/// Indexes the finalized blocks that are not indexed yet, then every block finalized from
+	/// now on.
+	///
+	/// Events can only be read from blocks whose state is kept, so a new index of an existing
+	/// chain starts at the oldest block with state; a complete one needs `--pruning archive`.
+	pub async fn run(self, client: Arc<FullClient>) {
+		let mut finality_notifications = client.finality_notification_stream();
+		self.catch_up(&client);
+		while finality_notifications.next().await.is_some() {
+			self.catch_up(&client);
+		}
+	}
+
+	fn catch_up(&self, client: &FullClient) {
+		if let Err(e) = self.index_until(client, client.info().finalized_number) {
+			log::warn!(target: LOG_TARGET, "Failed to index finalized blocks: {}", e);
+		}
+	}
+
+	fn index_until(&self, client: &FullClient, finalized: BlockNumber) -> Result<(), String> {
+		let mut next = self.first_block(client, finalized)?;
+		if next > finalized {
+			return Ok(())
+		}
+		while next <= finalized {
+			let hash = client
+				.hash(next)
+				.map_err(|e| e.to_string())?
+				.ok_or_else(|| format!("Finalized block #{} not found", next))?;
+			if let Err(e) = self.index_block(client, next, hash) {
+				log::warn!(target: LOG_TARGET, "Skipped block #{} ({}): {}", next, hash, e);
+			}
+			next += 1;
+			// Saved after every block, so that a walk that stops carries on from there. Indexing
+			// a block again overwrites its events, so the index stays consistent when the events
+			// are written but this is not.
+			self.db.insert(NEXT_BLOCK, next.encode()).map_err(|e| e.to_string())?;
+		}
+		self.db.flush().map_err(|e| e.to_string())?;
+		Ok(())
+	}
+
+	/// The next block to index or, for a new index, the oldest finalized block whose state is
+	/// kept.
+	fn first_block(
+		&self,
+		client: &FullClient,
+		finalized: BlockNumber,
+	) -> Result<BlockNumber, String> {
+		if let Some(next) = self.db.get(NEXT_BLOCK).map_err(|e| e.to_string())? {
+			return BlockNumber::decode(&mut &next[..]).map_err(|e| e.to_string())
+		}
+		// Pruning discards the states of the oldest blocks first, so the blocks with state are
+		// those from some block up to the finalized one.
+		let has_state = |number| client.state_at(&BlockId::Number(number)).is_ok();
+		let (mut oldest, mut newest) = (0, finalized);
+		while oldest < newest {
+			let middle = oldest + (newest - oldest) / 2;
+			if has_state(middle) {
+				newest = middle;
+			} else {
+				oldest = middle + 1;
+			}
+		}
+		if oldest > 0 {
+			log::info!(
+				target: LOG_TARGET,
+				"Indexing from block #{}, the oldest with state. Index the earlier blocks with \
+				`--pruning archive`.",
+				oldest,
+			);
+		}
+		Ok(oldest)
+	}
+
+	fn index_block(
+		&self,
+		client: &FullClient,
+		number: BlockNumber,
+		hash: Hash,
+	) -> Result<(), String> {
+		let at = BlockId::Hash(hash);
+		let records = events(client, &at)?;
+		if !records.iter().any(|record| matches!(record.event, Event::LaborModule(_))) {
+			return Ok(())
+		}
+		let time = client
+			.storage(&at, &StorageKey(pallet_timestamp::Now::<Runtime>::hashed_key().to_vec()))
+			.map_err(|e| e.to_string())?
+			.map(|data| Moment::decode(&mut &data.0[..]))
+			.transpose()
+			.map_err(|e| format!("Failed to decode the timestamp: {}", e))?
+			.unwrap_or_default();
+
+		for (index, record) in (0u32..).zip(records) {
+			let event = match record.event {
+				Event::LaborModule(event) => event,
+				_ => continue,
+			};
+			let (name, job, accounts, data) = match describe(&event) {
+				Some(description) => description,
+				None => continue,
+			};
+			let indexed = IndexedEvent {
+				block: number,
+				hash,
+				time,
+				index,
+				event: name.into(),
+				job,
+				accounts: accounts.iter().map(|account| account.to_ss58check()).collect(),
+				data,
+			};
+			self.insert(&indexed, &accounts).map_err(|e| e.to_string())?;
+		}
+		Ok(())
+	}
+
+	fn insert(&self, event: &IndexedEvent, accounts: &[AccountId]) -> Result<(), sled::Error> {
+		let key = event_key(event);
+		let value = serde_json::to_vec(event).expect("events serialize to JSON; qed");
+		self.events.insert(&key, value)?;
+		for account in accounts {
+			self.accounts.insert([&account.encode()[..], &key].concat(), &b""[..])?;
+		}
+		if let Some(job) = event.job {
+			self.jobs.insert([&job.to_be_bytes()[..], &key].concat(), &b""[..])?;
+		}
+		Ok(())
+	}
+
+	/// The indexed events that match the query, oldest first.
+	pub fn query(&self, query: &EventQuery) -> Result<Vec<IndexedEvent>, String> {
+		if query.limit == Some(0) {
+			return Err("The limit must be at least 1".into())
+		}
+		let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+		let account = query
+			.account
+			.as_ref()
+			.map(|account| {
+				AccountId::from_ss58check(account)
+					.map_err(|_| format!("Invalid address `{}`", account))
+			})
+			.transpose()?;
+		// Look the events up by job if both are given, as jobs name fewer events.
+		let (tree, prefix) = match (query.job, &account) {
+			(Some(job), _) => (&self.jobs, job.to_be_bytes().to_vec()),
+			(None, Some(account)) => (&self.accounts, account.encode()),
+			(None, None) => (&self.events, Vec::new()),
+		};
+
+		let start = [&prefix[..], &query.from.unwrap_or_default().to_be_bytes()].concat();
+		let entries = match query.to {
+			Some(to) => tree.range(start..[&prefix[..], &to.to_be_bytes()].concat()),
+			None => tree.range(start..),
+		};
+		let account = account.map(|account| account.to_ss58check());
+		let mut events = Vec::new();
+		for entry in entries {
+			if events.len() == limit {
+				break
+			}
+			let (key, value) = entry.map_err(|e| e.to_string())?;
+			if !key.starts_with(&prefix) {
+				break
+			}
+			let value = if prefix.is_empty() {
+				value
+			} else {
+				self.events
+					.get(&key[prefix.len()..])
+					.map_err(|e| e.to_string())?
+					.ok_or("The index is inconsistent, an event is missing")?
+			};
+			let event: IndexedEvent = serde_json::from_slice(&value).map_err(|e| e.to_string())?;
+			if account.as_ref().map_or(true, |account| event.accounts.contains(account)) {
+				events.push(event);
+			}
+		}
+		Ok(events)
+	}
+}
+
+/// The key of an event: block time, block number and event index, big-endian so that keys sort
+/// by time.
+fn event_key(event: &IndexedEvent) -> Vec<u8> {
+	[&event.time.to_be_bytes()[..], &event.block.to_be_bytes(), &event.index.to_be_bytes()].concat()
+}
+
+impl LaborIndexApi for LaborIndex {
+	fn indexed_events(&self, query: EventQuery) -> RpcResult<Vec<IndexedEvent>> {
+		self.query(&query).map_err(|message| RpcError {
+			code: ErrorCode::InvalidParams,
+			message,
+			data: None,
+		})
+	}
+}
+
+/// The name, job, accounts and remaining fields of an event. The events of the template calls
+/// are not indexed.
+fn describe(
+	event: &LaborEvent<Runtime>,
+) -> Option<(&'static str, Option<JobId>, Vec<AccountId>, serde_json::Value)> {
+	let none = json!({});
+	Some(match event {
+		LaborEvent::JobPosted(job, employer, payment) => (
+			"JobPosted",
+			Some(*job),
+			vec![employer.clone()],
+			json!({ "employer": employer.to_ss58check(), "payment": payment.to_string() }),
+		),
+		LaborEvent::PrivateJobPosted(job, payload_hash) =>
+			("PrivateJobPosted", Some(*job), vec![], json!({ "payloadHash": payload_hash })),
+		LaborEvent::ContentKeyShared(job, worker) => (
+			"ContentKeyShared",
+			Some(*job),
+			vec![worker.clone()],
+			json!({ "worker": worker.to_ss58check() }),
+		),
+		LaborEvent::JobApplied(job, worker) => (
+			"JobApplied",
+			Some(*job),
+			vec![worker.clone()],
+			json!({ "worker": worker.to_ss58check() }),
+		),
+		LaborEvent::JobAssigned(job, worker) => (
+			"JobAssigned",
+			Some(*job),
+			vec![worker.clone()],
+			json!({ "worker": worker.to_ss58check() }),
+		),
+		LaborEvent::JobCompleted(job, worker, payment) => (
+			"JobCompleted",
+			Some(*job),
+			vec![worker.clone()],
+			json!({ "worker": worker.to_ss58check(), "payment": payment.to_string() }),
+		),
+		LaborEvent::ReferralPaid(job, referrer, commission) => (
+			"ReferralPaid",
+			Some(*job),
+			vec![referrer.clone()],
+			json!({ "referrer": referrer.to_ss58check(), "commission": commission.to_string() }),
+		),
+		LaborEvent::JobCancelled(job) => ("JobCancelled", Some(*job), vec![], none),
+		LaborEvent::JobDisputed(job, who) => (
+			"JobDisputed",
+			Some(*job),
+			vec![who.clone()],
+			json!({ "who": who.to_ss58check() }),
+		),
+		LaborEvent::JobResolved(job, paid_to_worker) =>
+			("JobResolved", Some(*job), vec![], json!({ "paidToWorker": paid_to_worker })),
+		LaborEvent::WorkerBondSlashed(job, worker, bond) => (
+			"WorkerBondSlashed",
+			Some(*job),
+			vec![worker.clone()],
+			json!({ "worker": worker.to_ss58check(), "bond": bond.to_string() }),
+		),
+		LaborEvent::AccountBanned(who) =>
+			("AccountBanned", None, vec![who.clone()], json!({ "who": who.to_ss58check() })),
+		LaborEvent::AccountUnbanned(who) =>
+			("AccountUnbanned", None, vec![who.clone()], json!({ "who": who.to_ss58check() })),
+		LaborEvent::RecurringJobCreated(series, employer) => (
+			"RecurringJobCreated",
+			None,
+			vec![employer.clone()],
+			json!({ "series": series, "employer": employer.to_ss58check() }),
+		),
+		LaborEvent::RecurringJobPosted(series, job) =>
+			("RecurringJobPosted", Some(*job), vec![], json!({ "series": series })),
+		LaborEvent::RecurringJobStopped(series) =>
+			("RecurringJobStopped", None, vec![], json!({ "series": series })),
+		LaborEvent::JobFrozen(job) => ("JobFrozen", Some(*job), vec![], none),
+		LaborEvent::JobUnfrozen(job) => ("JobUnfrozen", Some(*job), vec![], none),
+		_ => return None,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_keyring::AccountKeyring;
+
+	fn indexed(index: u32, time: Moment, job: JobId, account: AccountKeyring) -> IndexedEvent {
+		IndexedEvent {
+			block: 1,
+			hash: Default::default(),
+			time,
+			index,
+			event: "JobApplied".into(),
+			job: Some(job),
+			accounts: vec![account.to_account_id().to_ss58check()],
+			data: json!({}),
+		}
+	}
+
+	#[test]
+	fn queries_by_account_job_and_time() {
+		let dir = tempfile::tempdir().unwrap();
+		let index = LaborIndex::open(dir.path()).unwrap();
+		let events = vec![
+			indexed(0, 1_000, 0, AccountKeyring::Alice),
+			indexed(1, 2_000, 1, AccountKeyring::Bob),
+			indexed(2, 3_000, 0, AccountKeyring::Bob),
+			indexed(3, 4_000, 1, AccountKeyring::Alice),
+		];
+		for event in &events {
+			let account = AccountId::from_ss58check(&event.accounts[0]).unwrap();
+			index.insert(event, &[account]).unwrap();
+		}
+		let bob = Some(AccountKeyring::Bob.to_account_id().to_ss58check());
+
+		let query = |query: EventQuery| index.query(&query).unwrap();
+		assert_eq!(query(EventQuery::default()), events);
+		assert_eq!(query(EventQuery { job: Some(0), ..Default::default() }), vec![
+			events[0].clone(),
+			events[2].clone(),
+		]);
+		assert_eq!(query(EventQuery { account: bob.clone(), ..Default::default() }), vec![
+			events[1].clone(),
+			events[2].clone(),
+		]);
+		assert_eq!(query(EventQuery { account: bob, job: Some(1), ..Default::default() }), vec![
+			events[1].clone(),
+		]);
+		assert_eq!(
+			query(EventQuery { from: Some(2_000), to: Some(4_000), ..Default::default() }),
+			events[1..3].to_vec(),
+		);
+		assert_eq!(
+			query(EventQuery { limit: Some(1), ..Default::default() }),
+			events[..1].to_vec(),
+		);
+		let invalid = EventQuery { account: Some("x".into()), ..Default::default() };
+		assert!(index.query(&invalid).is_err());
+		let invalid = EventQuery { limit: Some(0), ..Default::default() };
+		assert!(index.query(&invalid).is_err());
+	}
+}
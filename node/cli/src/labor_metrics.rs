@@ -0,0 +1,187 @@
+//! Prometheus metrics of the job market, kept up to date from the `pallet_labor` events of
+//! imported blocks.
+
+use crate::{
+	labor_storage::{events, jobs, read_job, Job},
+	service::FullClient,
+};
+use futures::StreamExt;
+use labor_node_runtime::{Event, Runtime};
+use node_primitives::{Balance, Block, Hash};
+use pallet_labor::{Event as LaborEvent, JobId, JobStatus};
+use prometheus_endpoint::{register, Gauge, PrometheusError, Registry, F64, U64};
+use sc_client_api::BlockchainEvents;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT},
+};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	sync::Arc,
+};
+
+const LOG_TARGET: &str = "labor-metrics";
+
+struct Metrics {
+	open_jobs: Gauge<U64>,
+	escrowed_value: Gauge<F64>,
+	disputes_in_progress: Gauge<U64>,
+	block_payouts: Gauge<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			open_jobs: register(
+				Gauge::new("labor_open_jobs", "Jobs accepting applications")?,
+				registry,
+			)?,
+			escrowed_value: register(
+				Gauge::new(
+					"labor_escrowed_value",
+					"Job payments and worker bonds reserved until the job is settled, in plancks",
+				)?,
+				registry,
+			)?,
+			disputes_in_progress: register(
+				Gauge::new("labor_disputes_in_progress", "Disputed jobs awaiting resolution")?,
+				registry,
+			)?,
+			block_payouts: register(
+				Gauge::new("labor_block_payouts", "Job payments released in the best block")?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// The jobs that hold escrow, with their status and escrowed value.
+#[derive(Default)]
+struct Escrow {
+	jobs: BTreeMap<JobId, (JobStatus, Balance)>,
+	/// The block the jobs are at.
+	best: Option<Hash>,
+}
+
+impl Escrow {
+	/// Moves the jobs to the given best block and returns the number of payouts in it.
+	///
+	/// Only the jobs named by events of the block are read again. When the block does not build
+	/// on the last one, after a reorg or blocks imported without notification, all jobs are.
+	fn import(
+		&mut self,
+		client: &FullClient,
+		header: &<Block as BlockT>::Header,
+	) -> Result<u64, String> {
+		let hash = header.hash();
+		let at = BlockId::Hash(hash);
+
+		let mut payouts = 0;
+		let mut changed = BTreeSet::new();
+		for record in events(client, &at)? {
+			let event = match record.event {
+				Event::LaborModule(event) => event,
+				_ => continue,
+			};
+			match event {
+				LaborEvent::JobCompleted(..) | LaborEvent::JobResolved(_, true) => payouts += 1,
+				_ => {},
+			}
+			changed.extend(job_of(&event));
+		}
+
+		if self.best == Some(*header.parent_hash()) {
+			for id in changed {
+				self.jobs.remove(&id);
+				if let Some(job) = read_job(client, &at, id)? {
+					self.insert(id, &job);
+				}
+			}
+		} else {
+			self.jobs.clear();
+			for (id, job) in jobs(client, &at)? {
+				self.insert(id, &job);
+			}
+		}
+		self.best = Some(hash);
+		Ok(payouts)
+	}
+
+	fn insert(&mut self, id: JobId, job: &Job) {
+		match job.status {
+			JobStatus::Open => {
+				self.jobs.insert(id, (job.status, job.payment));
+			},
+			// The bond of the worker is reserved from the assignment until the job is settled.
+			JobStatus::Assigned | JobStatus::Disputed => {
+				self.jobs.insert(id, (job.status, job.payment.saturating_add(job.worker_bond)));
+			},
+			JobStatus::Completed | JobStatus::Cancelled | JobStatus::Resolved => {},
+		}
+	}
+
+	fn report(&self, metrics: &Metrics) {
+		let count = |status| self.jobs.values().filter(|(s, _)| *s == status).count() as u64;
+		metrics.open_jobs.set(count(JobStatus::Open));
+		metrics.disputes_in_progress.set(count(JobStatus::Disputed));
+		// Precise up to 2^53 plancks, which is enough for a dashboard.
+		metrics.escrowed_value.set(self.jobs.values().map(|(_, value)| *value as f64).sum());
+	}
+}
+
+/// Registers the labor metrics and spawns the task that updates them on every new best block.
+pub fn spawn(
+	spawn_handle: &sc_service::SpawnTaskHandle,
+	client: Arc<FullClient>,
+	registry: &Registry,
+) -> Result<(), PrometheusError> {
+	let metrics = Metrics::register(registry)?;
+	let task = async move {
+		let mut imports = client.import_notification_stream();
+		let mut escrow = Escrow::default();
+		if let Some(header) = client.header(BlockId::Hash(client.info().best_hash)).ok().flatten() {
+			update(&mut escrow, &client, &header, &metrics);
+		}
+		while let Some(notification) = imports.next().await {
+			if notification.is_new_best {
+				update(&mut escrow, &client, &notification.header, &metrics);
+			}
+		}
+	};
+	spawn_handle.spawn("labor-metrics", task);
+	Ok(())
+}
+
+fn update(
+	escrow: &mut Escrow,
+	client: &FullClient,
+	header: &<Block as BlockT>::Header,
+	metrics: &Metrics,
+) {
+	match escrow.import(client, header) {
+		Ok(payouts) => {
+			metrics.block_payouts.set(payouts);
+			escrow.report(metrics);
+		},
+		Err(e) => {
+			// Read everything again at the next block.
+			escrow.best = None;
+			log::warn!(target: LOG_TARGET, "Failed to update the labor metrics: {}", e);
+		},
+	}
+}
+
+/// The job whose status or escrow an event may have changed.
+fn job_of(event: &LaborEvent<Runtime>) -> Option<JobId> {
+	match event {
+		LaborEvent::JobPosted(id, ..) |
+		LaborEvent::JobAssigned(id, _) |
+		LaborEvent::JobCompleted(id, ..) |
+		LaborEvent::JobCancelled(id) |
+		LaborEvent::JobDisputed(id, _) |
+		LaborEvent::JobResolved(id, _) |
+		LaborEvent::RecurringJobPosted(_, id) => Some(*id),
+		_ => None,
+	}
+}
@@ -0,0 +1,199 @@
+//! Portable proofs that a worker completed a job.
+//!
+//! The MMR leaf of every block commits to the jobs its parent completed. A [`CompletionProof`]
+//! carries that leaf with its MMR proof against the root at a later block, so whoever trusts the
+//! root can check it without access to the chain, with the `verify_proof_stateless` runtime API.
+
+use crate::service::FullClient;
+use codec::{Decode, Encode};
+use labor_node_runtime::Runtime;
+use node_primitives::{AccountId, Block, BlockNumber, Hash};
+use pallet_labor::{CompletedAt, CompletionLeafOf, JobId};
+use pallet_mmr_primitives::{
+	runtime_decl_for_MmrApi, EncodableOpaqueLeaf, MmrApi, OpaqueLeaf, Proof,
+};
+use sc_client_api::StorageProvider;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, hashing::twox_128, storage::StorageKey, Bytes, ExecutionContext};
+use sp_runtime::generic::BlockId;
+
+type Leaf = CompletionLeafOf<Runtime>;
+
+/// A proof that a worker completed a job.
+///
+/// Only `root` needs to be trusted; it is the `Mmr::RootHash` at `rootBlockHash`, which anyone
+/// can look up on a node they trust.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CompletionProof {
+	/// The completed job.
+	pub job: JobId,
+	/// The worker that completed the job, in SS58 format.
+	pub worker: String,
+	/// The payment the worker received, in the smallest unit.
+	pub payment: String,
+	/// The hash of the block that completed the job.
+	pub block_hash: Hash,
+	/// The SCALE encoded MMR leaf of the block.
+	pub leaf: Bytes,
+	/// The SCALE encoded MMR proof of the leaf.
+	pub proof: Bytes,
+	/// The MMR root the proof leads to.
+	pub root: Hash,
+	/// The number of the block with the root.
+	pub root_block: BlockNumber,
+	/// The hash of the block with the root.
+	pub root_block_hash: Hash,
+}
+
+/// Proves that the worker of a completed job completed it, against the MMR root at block `at`.
+///
+/// The leaves are read from the offchain database, which only has them if the node imported the
+/// blocks with `--enable-offchain-indexing true`.
+pub(crate) fn prove(client: &FullClient, at: Hash, job: JobId) -> Result<CompletionProof, String> {
+	let id = BlockId::Hash(at);
+	let root_block = client
+		.number(at)
+		.map_err(|e| e.to_string())?
+		.ok_or_else(|| format!("Unknown block {:?}", at))?;
+	let completed: BlockNumber = read(client, &id, CompletedAt::<Runtime>::hashed_key_for(job))?
+		.ok_or_else(|| format!("Job {} was not completed at block {:?}", job, at))?;
+	if completed >= root_block {
+		return Err(format!(
+			"Job {} was completed in block #{}, whose MMR leaf is added by the next block",
+			job, completed,
+		))
+	}
+	let leaves: u64 = read(client, &id, mmr_key("NumberOfLeaves"))?.unwrap_or_default();
+	let root: Hash = read(client, &id, mmr_key("RootHash"))?.unwrap_or_default();
+	// The leaf of block `n` is added by block `n + 1`, so block `at` added the last one.
+	let leaf_index = (leaves + completed as u64)
+		.checked_sub(root_block as u64)
+		.ok_or_else(|| format!("Block #{} predates the MMR", completed))?;
+
+	// Like the MMR RPC, generate the proof in an offchain context, with the leaves at hand.
+	let (leaf, proof) = client
+		.runtime_api()
+		.generate_proof_with_context(&id, ExecutionContext::OffchainCall(None), leaf_index)
+		.map_err(|e| e.to_string())?
+		.map_err(|e| format!("Failed to generate the MMR proof of leaf {}: {:?}", leaf_index, e))?;
+	let leaf = leaf.into_opaque_leaf().0;
+	let decoded = Leaf::decode(&mut &leaf[..]).map_err(|e| e.to_string())?;
+	let completion = decoded
+		.completions
+		.iter()
+		.find(|completion| completion.job == job)
+		.ok_or_else(|| format!("The MMR leaf of block #{} lacks job {}", completed, job))?;
+
+	Ok(CompletionProof {
+		job,
+		worker: completion.worker.to_ss58check(),
+		payment: completion.payment.to_string(),
+		block_hash: decoded.parent_hash,
+		leaf: leaf.into(),
+		proof: proof.encode().into(),
+		root,
+		root_block,
+		root_block_hash: at,
+	})
+}
+
+/// Checks that the root of `proof` is the trusted `root`, that its leaf is in the MMR with that
+/// root, and that it records the completion that `proof` claims.
+pub(crate) fn verify(proof: &CompletionProof, root: Hash) -> Result<(), String> {
+	// The leaf and proof alone may well lead to a root that the prover made up.
+	if proof.root != root {
+		return Err(format!("The proof is against another root, {:?}", proof.root))
+	}
+	let mmr_proof = Proof::<Hash>::decode(&mut &proof.proof[..])
+		.map_err(|e| format!("Invalid MMR proof: {}", e))?;
+	let opaque = EncodableOpaqueLeaf(OpaqueLeaf(proof.leaf.to_vec()));
+	// The native runtime, so no chain state is needed.
+	<Runtime as runtime_decl_for_MmrApi::MmrApi<Block, Hash>>::verify_proof_stateless(
+		proof.root,
+		opaque,
+		mmr_proof,
+	)
+	.map_err(|_| "The MMR proof does not lead to the root".to_string())?;
+
+	let leaf = Leaf::decode(&mut &proof.leaf[..]).map_err(|e| format!("Invalid leaf: {}", e))?;
+	if leaf.parent_hash != proof.block_hash {
+		return Err(format!("The leaf is not the one of block {:?}", proof.block_hash))
+	}
+	let worker = AccountId::from_ss58check(&proof.worker)
+		.map_err(|e| format!("Invalid worker {}: {:?}", proof.worker, e))?;
+	let recorded = leaf.completions.iter().any(|completion| {
+		completion.job == proof.job &&
+			completion.worker == worker &&
+			completion.payment.to_string() == proof.payment
+	});
+	if !recorded {
+		return Err(format!(
+			"The leaf does not record that {} completed job {}",
+			proof.worker, proof.job,
+		))
+	}
+	Ok(())
+}
+
+fn mmr_key(item: &str) -> Vec<u8> {
+	let mut key = twox_128(b"Mmr").to_vec();
+	key.extend(twox_128(item.as_bytes()));
+	key
+}
+
+fn read<T: Decode>(
+	client: &FullClient,
+	at: &BlockId<Block>,
+	key: Vec<u8>,
+) -> Result<Option<T>, String> {
+	client
+		.storage(at, &StorageKey(key))
+		.map_err(|e| e.to_string())?
+		.map(|data| T::decode(&mut &data.0[..]).map_err(|e| e.to_string()))
+		.transpose()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pallet_labor::{Completion, CompletionLeaf};
+	use sp_keyring::AccountKeyring;
+	use sp_runtime::traits::{BlakeTwo256, Hash as _};
+
+	#[test]
+	fn completions_are_verified_against_the_root() {
+		let bob = AccountKeyring::Bob.to_account_id();
+		let completion = Completion { job: 7, worker: bob.clone(), payment: 1000 };
+		let block_hash = Hash::repeat_byte(1);
+		let leaf = CompletionLeaf { parent_hash: block_hash, completions: vec![completion] };
+		let leaf = leaf.encode();
+		let mut proof = CompletionProof {
+			job: 7,
+			worker: bob.to_ss58check(),
+			payment: "1000".into(),
+			block_hash,
+			// The root of an MMR with a single leaf is the hash of the leaf.
+			root: BlakeTwo256::hash(&leaf),
+			leaf: leaf.into(),
+			proof: Proof::<Hash> { leaf_index: 0, leaf_count: 1, items: vec![] }.encode().into(),
+			root_block: 2,
+			root_block_hash: Hash::repeat_byte(2),
+		};
+		let root = proof.root;
+		assert_eq!(verify(&proof, root), Ok(()));
+		// A proof is only as good as the root it is checked against.
+		assert!(verify(&proof, Hash::repeat_byte(3)).is_err());
+
+		proof.worker = AccountKeyring::Alice.to_account_id().to_ss58check();
+		assert!(verify(&proof, root).is_err());
+		proof.worker = bob.to_ss58check();
+		proof.payment = "2000".into();
+		assert!(verify(&proof, root).is_err());
+		proof.payment = "1000".into();
+		proof.root = Hash::repeat_byte(3);
+		assert!(verify(&proof, root).is_err());
+	}
+}
@@ -0,0 +1,370 @@
+//! Labor RPC methods served next to those of `node_rpc`.
+
+use crate::{
+	labor_proof::{prove, CompletionProof},
+	labor_storage::{read_encoded_job, Job},
+	service::{FullClient, LightClient},
+};
+use codec::Decode;
+use futures::{
+	future::{self, BoxFuture},
+	stream, FutureExt, StreamExt, TryFutureExt, TryStreamExt,
+};
+use jsonrpc_core::{
+	futures::{Future as _, Sink as _},
+	Error as RpcError, ErrorCode, Result as RpcResult,
+};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use labor_node_runtime::Runtime;
+use node_primitives::{Block, BlockNumber, Hash};
+use pallet_labor::{JobId, Jobs, NextJobId};
+use sc_client_api::{
+	light::{future_header, Fetcher, RemoteBlockchain, RemoteReadRequest},
+	BlockchainEvents, StorageProvider,
+};
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::Serialize;
+use serde_json::json;
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, storage::StorageKey};
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use std::sync::Arc;
+
+/// The most jobs one `labor_getJobs` call returns.
+pub(crate) const MAX_JOBS: u32 = 100;
+
+/// Whether a block is the new best block or was finalized.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BlockStatus {
+	/// The block is the new best block.
+	Best,
+	/// The block was finalized.
+	Finalized,
+}
+
+/// The state of a job at a block.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobUpdate {
+	/// Whether the block is the new best block or was finalized.
+	pub status: BlockStatus,
+	/// The block number.
+	pub block: BlockNumber,
+	/// The block hash.
+	pub block_hash: Hash,
+	/// The job at the block, `null` if there is no job with the id.
+	pub job: Option<serde_json::Value>,
+}
+
+/// Labor RPC methods.
+#[rpc]
+pub trait LaborApi {
+	/// RPC metadata
+	type Metadata;
+
+	/// Subscribe to the state of a job.
+	///
+	/// Sends the job at the finalized and the best block first, then at every new best block
+	/// and every finalized block where it changed.
+	#[pubsub(subscription = "labor_job", subscribe, name = "labor_subscribeJob")]
+	fn subscribe_job(&self, metadata: Self::Metadata, subscriber: Subscriber<JobUpdate>, id: JobId);
+
+	/// Unsubscribe from the state of a job.
+	#[pubsub(subscription = "labor_job", unsubscribe, name = "labor_unsubscribeJob")]
+	fn unsubscribe_job(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> RpcResult<bool>;
+
+	/// A proof that the worker of a completed job completed it, against the MMR root at a block,
+	/// the best block by default.
+	#[rpc(name = "labor_proveCompletion")]
+	fn prove_completion(&self, job: JobId, at: Option<Hash>) -> RpcResult<CompletionProof>;
+}
+
+/// Labor job queries, served by full and light nodes.
+#[rpc]
+pub trait LaborJobsApi {
+	/// The job with the id at a block, the best block by default, with its id.
+	#[rpc(name = "labor_getJob")]
+	fn job(
+		&self,
+		id: JobId,
+		at: Option<Hash>,
+	) -> jsonrpc_core::BoxFuture<Option<serde_json::Value>>;
+
+	/// The jobs from id `start` on at a block, the best block by default, with their ids. Returns
+	/// up to `count` jobs, at most and by default 100.
+	#[rpc(name = "labor_getJobs")]
+	fn jobs(
+		&self,
+		start: Option<JobId>,
+		count: Option<u32>,
+		at: Option<Hash>,
+	) -> jsonrpc_core::BoxFuture<Vec<serde_json::Value>>;
+}
+
+/// Implements the labor RPC methods for a full node.
+pub struct Labor {
+	client: Arc<FullClient>,
+	subscriptions: SubscriptionManager,
+}
+
+impl Labor {
+	/// Creates the labor RPC handler, running subscriptions with `executor`.
+	pub fn new(client: Arc<FullClient>, executor: &SubscriptionTaskExecutor) -> Self {
+		Self { client, subscriptions: SubscriptionManager::new(Arc::new(executor.clone())) }
+	}
+}
+
+impl LaborApi for Labor {
+	type Metadata = sc_rpc::Metadata;
+
+	fn subscribe_job(&self, _: Self::Metadata, subscriber: Subscriber<JobUpdate>, id: JobId) {
+		let client = self.client.clone();
+		let best = client
+			.import_notification_stream()
+			.filter(|notification| future::ready(notification.is_new_best))
+			.map(|notification| {
+				(notification.hash, *notification.header.number(), BlockStatus::Best)
+			});
+		let finalized = client.finality_notification_stream().map(|notification| {
+			(notification.hash, *notification.header.number(), BlockStatus::Finalized)
+		});
+		// Read after opening the streams, so no block imported in between is missed. One that is
+		// in both is sent once, as updates that repeat the last one are skipped.
+		let info = client.info();
+		let current = stream::iter(vec![
+			(info.finalized_hash, info.finalized_number, BlockStatus::Finalized),
+			(info.best_hash, info.best_number, BlockStatus::Best),
+		]);
+
+		// The encoded job of the last best and finalized update, to skip blocks that left it
+		// unchanged.
+		let mut last_best = None;
+		let mut last_finalized = None;
+		let updates = current.chain(stream::select(best, finalized)).filter_map(
+			move |(hash, number, status)| {
+				let last = match status {
+					BlockStatus::Best => &mut last_best,
+					BlockStatus::Finalized => &mut last_finalized,
+				};
+				future::ready(job_update(&client, id, hash, number, status, last))
+			},
+		);
+		let updates = updates.map(Ok::<_, ()>).boxed().compat();
+
+		self.subscriptions.add(subscriber, |sink| {
+			sink.sink_map_err(|e| log::warn!("Error sending job updates: {:?}", e))
+				.send_all(updates)
+				// The stream only ends when the node stops or the subscriber is gone.
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_job(&self, _: Option<Self::Metadata>, id: SubscriptionId) -> RpcResult<bool> {
+		Ok(self.subscriptions.cancel(id))
+	}
+
+	fn prove_completion(&self, job: JobId, at: Option<Hash>) -> RpcResult<CompletionProof> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		prove(&self.client, at, job).map_err(|message| RpcError {
+			code: ErrorCode::InvalidParams,
+			message,
+			data: None,
+		})
+	}
+}
+
+/// The job at the block, unless it is the `last` one sent.
+fn job_update(
+	client: &FullClient,
+	id: JobId,
+	hash: Hash,
+	number: BlockNumber,
+	status: BlockStatus,
+	last: &mut Option<Option<Vec<u8>>>,
+) -> Option<Result<JobUpdate, RpcError>> {
+	let encoded = match read_encoded_job(client, &BlockId::Hash(hash), id) {
+		Ok(encoded) => encoded,
+		Err(e) => return Some(Err(internal_error(e))),
+	};
+	if last.as_ref() == Some(&encoded) {
+		return None
+	}
+	let job = match encoded.as_ref().map(|encoded| Job::decode(&mut &encoded[..])).transpose() {
+		Ok(job) => job,
+		Err(e) => return Some(Err(internal_error(e.to_string()))),
+	};
+	*last = Some(encoded);
+	let job = job.as_ref().map(job_value);
+	Some(Ok(JobUpdate { status, block: number, block_hash: hash, job }))
+}
+
+fn internal_error(message: String) -> RpcError {
+	RpcError { code: ErrorCode::InternalError, message, data: None }
+}
+
+/// Reads the storage of the job queries.
+trait ReadStorage: Send + Sync + 'static {
+	/// The block to answer at if the query names none.
+	fn best_hash(&self) -> Hash;
+
+	/// The values of `keys` at the block, in the same order.
+	fn read(
+		&self,
+		at: Hash,
+		keys: Vec<Vec<u8>>,
+	) -> BoxFuture<'static, Result<Vec<Option<Vec<u8>>>, String>>;
+}
+
+/// Reads the state of a full node.
+struct FullStorage(Arc<FullClient>);
+
+impl ReadStorage for FullStorage {
+	fn best_hash(&self) -> Hash {
+		self.0.info().best_hash
+	}
+
+	fn read(
+		&self,
+		at: Hash,
+		keys: Vec<Vec<u8>>,
+	) -> BoxFuture<'static, Result<Vec<Option<Vec<u8>>>, String>> {
+		let values = keys
+			.into_iter()
+			.map(|key| {
+				let value = self.0.storage(&BlockId::Hash(at), &StorageKey(key));
+				value.map(|value| value.map(|value| value.0)).map_err(|e| e.to_string())
+			})
+			.collect();
+		future::ready(values).boxed()
+	}
+}
+
+/// Reads the state of a light client from full nodes.
+///
+/// The fetcher only returns values whose storage proof it checked against the state root of the
+/// block header, so a light client does not need to trust the full node that answers.
+struct LightStorage<F> {
+	client: Arc<LightClient>,
+	remote_blockchain: Arc<dyn RemoteBlockchain<Block>>,
+	fetcher: Arc<F>,
+}
+
+impl<F: Fetcher<Block> + 'static> ReadStorage for LightStorage<F> {
+	fn best_hash(&self) -> Hash {
+		self.client.info().best_hash
+	}
+
+	fn read(
+		&self,
+		at: Hash,
+		keys: Vec<Vec<u8>>,
+	) -> BoxFuture<'static, Result<Vec<Option<Vec<u8>>>, String>> {
+		let header = future_header(&*self.remote_blockchain, &*self.fetcher, BlockId::Hash(at));
+		let fetcher = self.fetcher.clone();
+		async move {
+			let header = header
+				.await
+				.map_err(|e| e.to_string())?
+				.ok_or_else(|| format!("Unknown block {}", at))?;
+			let request =
+				RemoteReadRequest { block: at, header, keys: keys.clone(), retry_count: None };
+			let mut values = fetcher.remote_read(request).await.map_err(|e| e.to_string())?;
+			Ok(keys.iter().map(|key| values.remove(key).flatten()).collect())
+		}
+		.boxed()
+	}
+}
+
+/// Implements the labor job queries from the state of a full node or a light client.
+pub struct LaborJobs {
+	storage: Arc<dyn ReadStorage>,
+}
+
+impl LaborJobs {
+	/// Answers from the state of a full node.
+	pub fn full(client: Arc<FullClient>) -> Self {
+		Self { storage: Arc::new(FullStorage(client)) }
+	}
+
+	/// Answers from storage proofs that a light client fetches from full nodes.
+	pub fn light<F: Fetcher<Block> + 'static>(
+		client: Arc<LightClient>,
+		remote_blockchain: Arc<dyn RemoteBlockchain<Block>>,
+		fetcher: Arc<F>,
+	) -> Self {
+		Self { storage: Arc::new(LightStorage { client, remote_blockchain, fetcher }) }
+	}
+}
+
+impl LaborJobsApi for LaborJobs {
+	fn job(
+		&self,
+		id: JobId,
+		at: Option<Hash>,
+	) -> jsonrpc_core::BoxFuture<Option<serde_json::Value>> {
+		let at = at.unwrap_or_else(|| self.storage.best_hash());
+		let key = Jobs::<Runtime>::hashed_key_for(id);
+		let job = self.storage.read(at, vec![key]).map(|values| {
+			let encoded = values?.pop().flatten();
+			encoded.map(|encoded| decode_job(id, &encoded)).transpose()
+		});
+		Box::new(job.map_err(internal_error).compat())
+	}
+
+	fn jobs(
+		&self,
+		start: Option<JobId>,
+		count: Option<u32>,
+		at: Option<Hash>,
+	) -> jsonrpc_core::BoxFuture<Vec<serde_json::Value>> {
+		let at = at.unwrap_or_else(|| self.storage.best_hash());
+		let start = start.unwrap_or_default();
+		let count = count.unwrap_or(MAX_JOBS).min(MAX_JOBS);
+		let storage = self.storage.clone();
+		let jobs = async move {
+			let key = NextJobId::<Runtime>::hashed_key().to_vec();
+			let next = match storage.read(at, vec![key]).await?.pop().flatten() {
+				Some(encoded) => JobId::decode(&mut &encoded[..]).map_err(|e| e.to_string())?,
+				None => 0,
+			};
+			let ids = (start..next.min(start.saturating_add(count))).collect::<Vec<_>>();
+			let keys = ids.iter().map(|id| Jobs::<Runtime>::hashed_key_for(id)).collect();
+			let mut jobs = Vec::new();
+			for (id, encoded) in ids.into_iter().zip(storage.read(at, keys).await?) {
+				if let Some(encoded) = encoded {
+					jobs.push(decode_job(id, &encoded)?);
+				}
+			}
+			Ok(jobs)
+		};
+		Box::new(jobs.map_err(internal_error).boxed().compat())
+	}
+}
+
+/// The encoded job as JSON, with its id.
+fn decode_job(id: JobId, encoded: &[u8]) -> Result<serde_json::Value, String> {
+	let job = Job::decode(&mut &encoded[..]).map_err(|e| e.to_string())?;
+	let mut job = job_value(&job);
+	job["id"] = json!(id);
+	Ok(job)
+}
+
+/// A job as JSON, without its id.
+pub(crate) fn job_value(job: &Job) -> serde_json::Value {
+	// Balances are printed as strings, they do not fit into a JSON number.
+	json!({
+		"employer": job.employer.to_ss58check(),
+		"worker": job.worker.as_ref().map(|w| w.to_ss58check()),
+		"payment": job.payment.to_string(),
+		"workerBond": job.worker_bond.to_string(),
+		"referrer": job.referrer.as_ref().map(|r| r.to_ss58check()),
+		"status": format!("{:?}", job.status),
+		"details": String::from_utf8_lossy(&job.details),
+	})
+}
@@ -0,0 +1,67 @@
+//! Reading the `pallet_labor` storage and the events of a block from the state of a full node.
+
+use crate::service::FullClient;
+use codec::Decode;
+use frame_support::storage::StoragePrefixedMap;
+use labor_node_runtime::{Event, Runtime};
+use node_primitives::{Block, Hash};
+use pallet_labor::{JobId, JobOf, Jobs};
+use sc_client_api::StorageProvider;
+use sp_core::storage::StorageKey;
+use sp_runtime::generic::BlockId;
+
+pub(crate) type Job = JobOf<Runtime>;
+
+pub(crate) type EventRecord = frame_system::EventRecord<Event, Hash>;
+
+/// Length of a `Blake2_128Concat` hash before the encoded key.
+pub(crate) const BLAKE2_128_LEN: usize = 16;
+
+/// The events of the block, decoded with the types of the native runtime.
+pub(crate) fn events(client: &FullClient, at: &BlockId<Block>) -> Result<Vec<EventRecord>, String> {
+	let key = StorageKey(frame_system::Events::<Runtime>::hashed_key().to_vec());
+	match client.storage(at, &key).map_err(|e| e.to_string())? {
+		Some(data) => Vec::<EventRecord>::decode(&mut &data.0[..])
+			.map_err(|e| format!("Failed to decode the events: {}", e)),
+		None => Ok(Vec::new()),
+	}
+}
+
+/// The encoded job at the block.
+pub(crate) fn read_encoded_job(
+	client: &FullClient,
+	at: &BlockId<Block>,
+	id: JobId,
+) -> Result<Option<Vec<u8>>, String> {
+	let key = StorageKey(Jobs::<Runtime>::hashed_key_for(id));
+	client.storage(at, &key).map(|data| data.map(|data| data.0)).map_err(|e| e.to_string())
+}
+
+/// The job at the block.
+pub(crate) fn read_job(
+	client: &FullClient,
+	at: &BlockId<Block>,
+	id: JobId,
+) -> Result<Option<Job>, String> {
+	read_encoded_job(client, at, id)?
+		.map(|data| Job::decode(&mut &data[..]))
+		.transpose()
+		.map_err(|e| format!("Failed to decode job {}: {}", id, e))
+}
+
+/// All jobs at the block, ordered by id.
+pub(crate) fn jobs(client: &FullClient, at: &BlockId<Block>) -> Result<Vec<(JobId, Job)>, String> {
+	let prefix = Jobs::<Runtime>::final_prefix();
+	let mut jobs = client
+		.storage_pairs(at, &StorageKey(prefix.to_vec()))
+		.map_err(|e| e.to_string())?
+		.into_iter()
+		.map(|(key, value)| {
+			let id = JobId::decode(&mut &key.0[prefix.len() + BLAKE2_128_LEN..])?;
+			Ok((id, Job::decode(&mut &value.0[..])?))
+		})
+		.collect::<Result<Vec<_>, codec::Error>>()
+		.map_err(|e| format!("Failed to decode the jobs: {}", e))?;
+	jobs.sort_by_key(|(id, _)| *id);
+	Ok(jobs)
+}
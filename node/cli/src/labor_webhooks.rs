@@ -0,0 +1,363 @@
+//! The `--labor-webhooks` service: POSTs labor events of finalized blocks to HTTP endpoints.
+//!
+//! Every endpoint has a delivery cursor, the next event to deliver, kept in
+//! `<base path>/chains/<chain id>/labor-webhooks.json`. Events are delivered in order, one at a
+//! time, and a failed delivery is retried with exponential backoff until the endpoint accepts it,
+//! so an endpoint sees every event at least once.
+
+use crate::{
+	labor_storage::{events, read_job},
+	service::FullClient,
+};
+use futures::{future, StreamExt};
+use hyper::{client::HttpConnector, header::CONTENT_TYPE, Body, Client, Request, Uri};
+use hyper_rustls::HttpsConnector;
+use labor_node_runtime::Event;
+use node_primitives::{BlockNumber, Hash};
+use pallet_labor::Event as LaborEvent;
+use parking_lot::Mutex;
+use sc_client_api::BlockchainEvents;
+use sc_service::{config::Configuration, error::Error as ServiceError, SpawnTaskHandle};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sp_blockchain::HeaderBackend;
+use sp_core::crypto::Ss58Codec;
+use sp_runtime::generic::BlockId;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::Duration,
+};
+
+const LOG_TARGET: &str = "labor-webhooks";
+
+/// The delay before the first retry of a failed delivery.
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The longest delay between retries.
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// How long an endpoint may take to respond before the delivery counts as failed.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+type HttpClient = Client<HttpsConnector<HttpConnector>>;
+
+/// The kinds of events that can be delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventKind {
+	/// A worker was assigned to a job.
+	JobAssigned,
+	/// The payment of a job went to its worker, on approval or by resolving a dispute.
+	PaymentReleased,
+	/// A party disputed a job.
+	DisputeOpened,
+}
+
+/// The webhooks configuration file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WebhooksConfig {
+	#[serde(rename = "endpoint")]
+	endpoints: Vec<EndpointConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EndpointConfig {
+	/// Identifies the delivery cursor of the endpoint, so the URL can change.
+	name: String,
+	url: String,
+	/// The events to deliver, all by default.
+	#[serde(default = "all_kinds")]
+	events: Vec<EventKind>,
+}
+
+fn all_kinds() -> Vec<EventKind> {
+	vec![EventKind::JobAssigned, EventKind::PaymentReleased, EventKind::DisputeOpened]
+}
+
+/// The next event to deliver to an endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Cursor {
+	block: BlockNumber,
+	event: u32,
+}
+
+/// The delivery cursors of all endpoints, by name, saved on every change.
+struct Cursors {
+	path: PathBuf,
+	cursors: Mutex<BTreeMap<String, Cursor>>,
+}
+
+impl Cursors {
+	fn load(path: PathBuf) -> Result<Self, String> {
+		let cursors = match std::fs::read(&path) {
+			Ok(json) => serde_json::from_slice(&json).map_err(|e| e.to_string())?,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+			Err(e) => return Err(e.to_string()),
+		};
+		Ok(Self { path, cursors: Mutex::new(cursors) })
+	}
+
+	fn get(&self, name: &str) -> Option<Cursor> {
+		self.cursors.lock().get(name).copied()
+	}
+
+	/// Sets the cursor of an endpoint and saves all cursors, replacing the file at once so that a
+	/// crash never leaves it half written.
+	fn set(&self, name: &str, cursor: Cursor) -> Result<(), String> {
+		let mut cursors = self.cursors.lock();
+		cursors.insert(name.into(), cursor);
+		let json = serde_json::to_vec_pretty(&*cursors).map_err(|e| e.to_string())?;
+		let tmp = self.path.with_extension("json.tmp");
+		std::fs::write(&tmp, json).map_err(|e| e.to_string())?;
+		std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+	}
+}
+
+struct Endpoint {
+	name: String,
+	url: Uri,
+	events: Vec<EventKind>,
+}
+
+/// The endpoints of `--labor-webhooks` with their delivery cursors.
+pub struct LaborWebhooks {
+	endpoints: Vec<Endpoint>,
+	cursors: Arc<Cursors>,
+}
+
+impl LaborWebhooks {
+	/// Reads the endpoints from the TOML file at `path` and the delivery cursors of the chain of
+	/// `config`.
+	pub fn load(path: &Path, config: &Configuration) -> Result<Self, ServiceError> {
+		let error = |e: String| {
+			ServiceError::Other(format!("Invalid labor webhooks {}: {}", path.display(), e))
+		};
+		let file = std::fs::read(path).map_err(|e| error(e.to_string()))?;
+		let file: WebhooksConfig = toml::from_slice(&file).map_err(|e| error(e.to_string()))?;
+		if file.endpoints.is_empty() {
+			return Err(error("no `[[endpoint]]`".into()))
+		}
+
+		let mut names = BTreeSet::new();
+		let mut endpoints = Vec::new();
+		for endpoint in file.endpoints {
+			if !names.insert(endpoint.name.clone()) {
+				return Err(error(format!("more than one endpoint named `{}`", endpoint.name)))
+			}
+			let url: Uri = endpoint
+				.url
+				.parse()
+				.map_err(|e| error(format!("invalid URL `{}`: {}", endpoint.url, e)))?;
+			if !matches!(url.scheme_str(), Some("http") | Some("https")) {
+				return Err(error(format!("`{}` is no HTTP or HTTPS URL", endpoint.url)))
+			}
+			endpoints.push(Endpoint { name: endpoint.name, url, events: endpoint.events });
+		}
+
+		let dir = config
+			.base_path
+			.as_ref()
+			.map(|base_path| base_path.config_dir(config.chain_spec.id()))
+			.ok_or_else(|| error("the delivery cursors need a base path".into()))?;
+		std::fs::create_dir_all(&dir).map_err(|e| error(e.to_string()))?;
+		let cursors = Cursors::load(dir.join("labor-webhooks.json")).map_err(error)?;
+		Ok(Self { endpoints, cursors: Arc::new(cursors) })
+	}
+
+	/// Spawns a delivery task for every endpoint.
+	///
+	/// An endpoint without a cursor starts with the events of the next finalized block.
+	pub fn spawn(self, spawn_handle: &SpawnTaskHandle, client: Arc<FullClient>) {
+		let http = Client::builder().build(HttpsConnector::with_native_roots());
+		for endpoint in self.endpoints {
+			let delivery = Delivery {
+				client: client.clone(),
+				http: http.clone(),
+				cursors: self.cursors.clone(),
+				endpoint,
+			};
+			spawn_handle.spawn("labor-webhooks", delivery.run());
+		}
+	}
+}
+
+/// Delivers the events of finalized blocks to one endpoint.
+struct Delivery {
+	client: Arc<FullClient>,
+	http: HttpClient,
+	cursors: Arc<Cursors>,
+	endpoint: Endpoint,
+}
+
+impl Delivery {
+	async fn run(self) {
+		let mut finality_notifications = self.client.finality_notification_stream();
+		let name = &self.endpoint.name;
+		let mut cursor = self.cursors.get(name).unwrap_or_else(|| Cursor {
+			block: self.client.info().finalized_number + 1,
+			event: 0,
+		});
+		loop {
+			while cursor.block <= self.client.info().finalized_number {
+				cursor = self.deliver_block(cursor).await;
+				if let Err(e) = self.cursors.set(name, cursor) {
+					log::warn!(target: LOG_TARGET, "Failed to save the cursor of {}: {}", name, e);
+				}
+			}
+			if finality_notifications.next().await.is_none() {
+				return
+			}
+		}
+	}
+
+	/// Delivers the events of the cursor's block from the cursor on, and returns the cursor of
+	/// the next block.
+	async fn deliver_block(&self, cursor: Cursor) -> Cursor {
+		let next_block = Cursor { block: cursor.block + 1, event: 0 };
+		for (index, payload) in self.read_payloads(cursor.block).await {
+			if index < cursor.event {
+				continue
+			}
+			self.post(&payload).await;
+			let delivered = Cursor { block: cursor.block, event: index + 1 };
+			if let Err(e) = self.cursors.set(&self.endpoint.name, delivered) {
+				log::warn!(target: LOG_TARGET, "Failed to save a delivery cursor: {}", e);
+			}
+		}
+		next_block
+	}
+
+	/// The payloads of the block, read again with exponential backoff until that succeeds.
+	///
+	/// No block is skipped, so the delivery stops at a block whose state was pruned. Nodes that
+	/// deliver webhooks should keep all states with `--pruning archive`.
+	async fn read_payloads(&self, number: BlockNumber) -> Vec<(u32, serde_json::Value)> {
+		let mut backoff = MIN_BACKOFF;
+		loop {
+			let error = match self.payloads(number) {
+				Ok(payloads) => return payloads,
+				Err(e) => e,
+			};
+			log::warn!(
+				target: LOG_TARGET,
+				"Failed to read the events of block #{} for {} ({}), retrying in {:?}",
+				number,
+				self.endpoint.name,
+				error,
+				backoff,
+			);
+			futures_timer::Delay::new(backoff).await;
+			backoff = (backoff * 2).min(MAX_BACKOFF);
+		}
+	}
+
+	/// The payloads of the events of the block that the endpoint subscribed to, with the indices
+	/// of the events.
+	fn payloads(&self, number: BlockNumber) -> Result<Vec<(u32, serde_json::Value)>, String> {
+		let hash = self
+			.client
+			.hash(number)
+			.map_err(|e| e.to_string())?
+			.ok_or("the block is not finalized")?;
+		let at = BlockId::Hash(hash);
+		let records = events(&self.client, &at)?;
+		// The jobs that paid a referral commission in the block.
+		let referred = records
+			.iter()
+			.filter_map(|record| match record.event {
+				Event::LaborModule(LaborEvent::ReferralPaid(job, ..)) => Some(job),
+				_ => None,
+			})
+			.collect::<BTreeSet<_>>();
+		let mut payloads = Vec::new();
+		for (index, record) in (0u32..).zip(records) {
+			let event = match record.event {
+				Event::LaborModule(event) => event,
+				_ => continue,
+			};
+			let (kind, mut payload) = match event {
+				LaborEvent::JobAssigned(job, worker) => (
+					EventKind::JobAssigned,
+					json!({ "job": job, "worker": worker.to_ss58check() }),
+				),
+				LaborEvent::JobCompleted(job, worker, payment) => (
+					EventKind::PaymentReleased,
+					json!({
+						"job": job,
+						"worker": worker.to_ss58check(),
+						"payment": payment.to_string(),
+						"disputed": false,
+					}),
+				),
+				// The event names neither the worker nor the amount, so they are read from the job.
+				// As on approval, the worker received the payment less the referral commission.
+				LaborEvent::JobResolved(job, true) => {
+					let resolved = read_job(&self.client, &at, job)?
+						.ok_or_else(|| format!("Resolved job {} not found", job))?;
+					let commission = if referred.contains(&job) {
+						resolved.referral_commission * resolved.payment
+					} else {
+						0
+					};
+					(
+						EventKind::PaymentReleased,
+						json!({
+							"job": job,
+							"worker": resolved.worker.map(|worker| worker.to_ss58check()),
+							"payment": resolved.payment.saturating_sub(commission).to_string(),
+							"disputed": true,
+						}),
+					)
+				},
+				LaborEvent::JobDisputed(job, by) => (
+					EventKind::DisputeOpened,
+					json!({ "job": job, "by": by.to_ss58check() }),
+				),
+				_ => continue,
+			};
+			if !self.endpoint.events.contains(&kind) {
+				continue
+			}
+			payload["event"] = json!(kind);
+			payload["block"] = json!(number);
+			payload["blockHash"] = json!(hash);
+			payload["eventIndex"] = json!(index);
+			payloads.push((index, payload));
+		}
+		Ok(payloads)
+	}
+
+	/// POSTs the payload until the endpoint accepts it with a 2xx status.
+	async fn post(&self, payload: &serde_json::Value) {
+		let body = payload.to_string();
+		let mut backoff = MIN_BACKOFF;
+		loop {
+			let request = Request::post(self.endpoint.url.clone())
+				.header(CONTENT_TYPE, "application/json")
+				.body(Body::from(body.clone()))
+				.expect("the URL and header are valid; qed");
+			let timeout = futures_timer::Delay::new(REQUEST_TIMEOUT);
+			let error = match future::select(self.http.request(request), timeout).await {
+				future::Either::Left((Ok(response), _)) if response.status().is_success() => {
+					return
+				},
+				future::Either::Left((Ok(response), _)) => format!("status {}", response.status()),
+				future::Either::Left((Err(e), _)) => e.to_string(),
+				future::Either::Right(_) => "timed out".into(),
+			};
+			log::warn!(
+				target: LOG_TARGET,
+				"Delivery to {} failed ({}), retrying in {:?}",
+				self.endpoint.name,
+				error,
+				backoff,
+			);
+			futures_timer::Delay::new(backoff).await;
+			backoff = (backoff * 2).min(MAX_BACKOFF);
+		}
+	}
+}
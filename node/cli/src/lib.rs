@@ -32,6 +32,10 @@
 
 pub mod chain_spec;
 
+mod labor_metrics;
+mod labor_proof;
+mod labor_rpc;
+mod labor_storage;
 #[macro_use]
 mod service;
 #[cfg(feature = "browser")]
@@ -40,6 +44,28 @@ mod browser;
 mod cli;
 #[cfg(feature = "cli")]
 mod command;
+#[cfg(feature = "cli")]
+mod extrinsic;
+#[cfg(feature = "cli")]
+mod generate_spec;
+#[cfg(feature = "cli")]
+mod job_crypto;
+#[cfg(feature = "cli")]
+mod labor;
+#[cfg(feature = "cli")]
+mod labor_index;
+#[cfg(feature = "cli")]
+mod labor_webhooks;
+#[cfg(feature = "cli")]
+mod manual_seal;
+#[cfg(feature = "cli")]
+mod prepare_testnet;
+#[cfg(feature = "cli")]
+mod session_keys;
+#[cfg(feature = "cli")]
+mod storage;
+#[cfg(feature = "cli")]
+mod tx;
 
 #[cfg(feature = "browser")]
 pub use browser::*;
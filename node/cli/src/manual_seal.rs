@@ -0,0 +1,164 @@
+//! Development service that authors blocks with manual-seal instead of BABE and GRANDPA.
+
+use crate::{
+	labor_index::{LaborIndex, LaborIndexApi},
+	labor_webhooks::LaborWebhooks,
+	service::{new_partial, LaborConfig},
+	Sealing,
+};
+use futures::{channel::mpsc, prelude::*, stream};
+use node_primitives::Hash;
+use sc_consensus_manual_seal::{
+	consensus::babe::BabeConsensusDataProvider,
+	rpc::{ManualSeal, ManualSealApi},
+	EngineCommand, ManualSealParams,
+};
+use sc_service::{config::Configuration, error::Error as ServiceError, ChainType, TaskManager};
+use sp_transaction_pool::TransactionPool;
+use std::{pin::Pin, time::Duration};
+
+type Commands = Pin<Box<dyn Stream<Item = EngineCommand<Hash>> + Send>>;
+
+/// Builds a full service for a development chain that authors blocks as `sealing` says.
+///
+/// Blocks still carry BABE pre-digests derived from the development keys in the keystore, so the
+/// runtime sees the same slots and epochs as with BABE. Every sealed block is finalized at once.
+pub fn new_manual_seal(
+	config: Configuration,
+	sealing: Sealing,
+	labor: LaborConfig,
+) -> Result<TaskManager, ServiceError> {
+	if config.chain_spec.chain_type() != ChainType::Development {
+		return Err(ServiceError::Other(
+			"`--sealing` is only available for development chains".into(),
+		))
+	}
+
+	let sc_service::PartialComponents {
+		client,
+		backend,
+		mut task_manager,
+		import_queue,
+		keystore_container,
+		select_chain,
+		transaction_pool,
+		inherent_data_providers: _,
+		other: (rpc_extensions_builder, import_setup, _, mut telemetry),
+	} = new_partial(&config)?;
+	let (block_import, _, babe_link) = import_setup;
+
+	let (network, network_status_sinks, system_rpc_tx, network_starter) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue,
+			on_demand: None,
+			block_announce_validator_builder: None,
+		})?;
+
+	if config.offchain_worker.enabled {
+		sc_service::build_offchain_workers(
+			&config, task_manager.spawn_handle(), client.clone(), network.clone(),
+		);
+	}
+
+	let prometheus_registry = config.prometheus_registry().cloned();
+	let labor_index = labor.index_path.as_deref().map(LaborIndex::open).transpose()?;
+	let labor_webhooks = labor
+		.webhooks_path
+		.as_deref()
+		.map(|path| LaborWebhooks::load(path, &config))
+		.transpose()?;
+	let (rpc_sink, rpc_commands) = mpsc::channel(16);
+	let rpc_extensions_builder = {
+		let labor_index = labor_index.clone();
+		move |deny_unsafe, subscription_executor| {
+			let mut io = rpc_extensions_builder(deny_unsafe, subscription_executor);
+			io.extend_with(ManualSealApi::to_delegate(ManualSeal::new(rpc_sink.clone())));
+			if let Some(labor_index) = &labor_index {
+				io.extend_with(LaborIndexApi::to_delegate(labor_index.clone()));
+			}
+			io
+		}
+	};
+
+	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+		config,
+		backend,
+		client: client.clone(),
+		keystore: keystore_container.sync_keystore(),
+		network,
+		rpc_extensions_builder: Box::new(rpc_extensions_builder),
+		transaction_pool: transaction_pool.clone(),
+		task_manager: &mut task_manager,
+		on_demand: None,
+		remote_blockchain: None,
+		network_status_sinks,
+		system_rpc_tx,
+		telemetry: telemetry.as_mut(),
+	})?;
+
+	if let Some(registry) = prometheus_registry.as_ref() {
+		crate::labor_metrics::spawn(&task_manager.spawn_handle(), client.clone(), registry)?;
+	}
+	if let Some(labor_index) = labor_index {
+		task_manager.spawn_handle().spawn_blocking("labor-index", labor_index.run(client.clone()));
+	}
+	if let Some(labor_webhooks) = labor_webhooks {
+		labor_webhooks.spawn(&task_manager.spawn_handle(), client.clone());
+	}
+
+	let commands: Commands = match sealing {
+		Sealing::Manual => Box::pin(rpc_commands),
+		Sealing::Instant => {
+			let imports = transaction_pool.import_notification_stream().map(|_| seal(false));
+			Box::pin(stream::select(rpc_commands, imports))
+		},
+		Sealing::Interval(millis) => {
+			let ticks = stream::unfold((), move |()| async move {
+				futures_timer::Delay::new(Duration::from_millis(millis)).await;
+				Some((seal(true), ()))
+			});
+			Box::pin(stream::select(rpc_commands, ticks))
+		},
+	};
+
+	let inherent_data_providers = sp_inherents::InherentDataProviders::new();
+	let consensus_data_provider = BabeConsensusDataProvider::new(
+		client.clone(),
+		keystore_container.sync_keystore(),
+		&inherent_data_providers,
+		babe_link.epoch_changes().clone(),
+		babe_link.config().genesis_authorities.clone(),
+	)
+	.map_err(|e| ServiceError::Other(e.to_string()))?;
+
+	let proposer = sc_basic_authorship::ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry.as_ref(),
+		telemetry.as_ref().map(|x| x.handle()),
+	);
+
+	let authorship = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+		block_import,
+		env: proposer,
+		client,
+		pool: transaction_pool.pool().clone(),
+		commands_stream: commands,
+		select_chain,
+		consensus_data_provider: Some(Box::new(consensus_data_provider)),
+		inherent_data_providers,
+	});
+	task_manager.spawn_essential_handle().spawn_blocking("manual-seal", authorship);
+
+	network_starter.start_network();
+	Ok(task_manager)
+}
+
+fn seal(create_empty: bool) -> EngineCommand<Hash> {
+	EngineCommand::SealNewBlock { create_empty, finalize: true, parent_hash: None, sender: None }
+}
@@ -0,0 +1,153 @@
+//! The `prepare-testnet` command: validator keys, keystores and a chain spec from one secret.
+
+use crate::{
+	chain_spec::{testnet_genesis, AccountId, ChainSpec, DEFAULT_NOMINATION_SEED},
+	generate_spec::{account_from_suri, check_sudo},
+	PrepareTestnetCmd,
+};
+use codec::Encode;
+use grandpa_primitives::AuthorityId as GrandpaId;
+use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
+use sc_cli::Error;
+use sc_keystore::LocalKeystore;
+use sc_service::ChainType;
+use serde_json::map::Map;
+use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
+use sp_consensus_babe::AuthorityId as BabeId;
+use sp_core::{
+	bytes::to_hex,
+	crypto::{key_types, KeyTypeId, Pair, Public},
+};
+use sp_keystore::SyncCryptoStore;
+
+type AuthorityKeys = (AccountId, AccountId, GrandpaId, BabeId, ImOnlineId, AuthorityDiscoveryId);
+
+/// The keys of a validator.
+struct Validator {
+	authority: AuthorityKeys,
+	/// The session keys as key type, hex seed and public key, for the keystore.
+	session_keys: Vec<(KeyTypeId, String, Vec<u8>)>,
+}
+
+impl PrepareTestnetCmd {
+	/// Run the command.
+	pub fn run(&self) -> Result<(), Error> {
+		if self.validators == 0 {
+			return Err(Error::Input("At least one validator is required".into()))
+		}
+		let secret = std::fs::read_to_string(&self.secret_file)?;
+		let secret = secret.trim();
+		if secret.is_empty() {
+			return Err(Error::Input(format!("{} is empty", self.secret_file.display())))
+		}
+
+		// Errors of the derivation would show the secret.
+		let invalid_secret =
+			|_| Error::Input("The secret file holds no valid secret phrase or seed".into());
+		let validators = (1..=self.validators)
+			.map(|index| validator(secret, index))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(invalid_secret)?;
+		let authorities = validators.iter().map(|v| v.authority.clone()).collect::<Vec<_>>();
+		// The stashes of the authorities are endowed by `testnet_genesis`.
+		let sudo = if self.no_sudo {
+			None
+		} else {
+			Some(account_from_suri(&format!("{}//sudo", secret)).map_err(invalid_secret)?)
+		};
+		check_sudo(&sudo).map_err(Error::Input)?;
+		let endowed = sudo.iter().cloned().collect::<Vec<_>>();
+
+		let mut properties = Map::new();
+		properties.insert("tokenSymbol".into(), "LBR".into());
+		properties.insert("tokenDecimals".into(), 6.into());
+		let spec = ChainSpec::from_genesis(
+			&self.name,
+			&self.id,
+			ChainType::Live,
+			move || {
+				testnet_genesis(
+					authorities.clone(),
+					vec![],
+					#[cfg(feature = "sudo")] sudo.clone().expect("checked by `check_sudo`; qed"),
+					Some(endowed.clone()),
+					#[cfg(feature = "contracts")] false,
+					DEFAULT_NOMINATION_SEED,
+				)
+			},
+			vec![],
+			None,
+			None,
+			Some(properties),
+			Default::default(),
+		);
+
+		let json = sc_service::ChainSpec::as_json(&spec, true).map_err(Error::Input)?;
+		std::fs::create_dir_all(&self.output)?;
+		let spec_path = self.output.join("chain_spec.json");
+		std::fs::write(&spec_path, json)?;
+		println!("Chain spec: {}", spec_path.display());
+
+		for (index, validator) in (1..).zip(&validators) {
+			// The keystore of a node is at `<base path>/chains/<chain id>/keystore`.
+			let base_path = self.output.join(format!("validator-{}", index));
+			let keystore_path = base_path.join("chains").join(&self.id).join("keystore");
+			let keystore = LocalKeystore::open(keystore_path, None)?;
+			for (key_type, seed, public) in &validator.session_keys {
+				SyncCryptoStore::insert_unknown(&keystore, *key_type, seed, public)
+					.map_err(|_| Error::KeyStoreOperation)?;
+			}
+			println!(
+				"Validator {}: stash {}, controller {}, base path {}",
+				index,
+				validator.authority.0,
+				validator.authority.1,
+				base_path.display(),
+			);
+		}
+		Ok(())
+	}
+}
+
+/// Derives the keys of the validator `index` from `<secret>//<index>//<key>`.
+fn validator(secret: &str, index: u32) -> Result<Validator, String> {
+	let suri = |key: &str| format!("{}//{}//{}", secret, index, key);
+	let (grandpa, grandpa_seed) = session_key::<GrandpaId>(&suri("grandpa"))?;
+	let (babe, babe_seed) = session_key::<BabeId>(&suri("babe"))?;
+	let (im_online, im_online_seed) = session_key::<ImOnlineId>(&suri("im_online"))?;
+	let (authority_discovery, authority_discovery_seed) =
+		session_key::<AuthorityDiscoveryId>(&suri("authority_discovery"))?;
+
+	Ok(Validator {
+		session_keys: vec![
+			(key_types::GRANDPA, grandpa_seed, grandpa.encode()),
+			(key_types::BABE, babe_seed, babe.encode()),
+			(key_types::IM_ONLINE, im_online_seed, im_online.encode()),
+			(
+				key_types::AUTHORITY_DISCOVERY,
+				authority_discovery_seed,
+				authority_discovery.encode(),
+			),
+		],
+		authority: (
+			account_from_suri(&suri("stash"))?,
+			account_from_suri(&suri("controller"))?,
+			grandpa,
+			babe,
+			im_online,
+			authority_discovery,
+		),
+	})
+}
+
+/// The public key derived from `suri` and the hex seed of its pair. The keystore holds the seed
+/// instead of the URI, which would hold the secret of every validator.
+fn session_key<TPublic: Public>(
+	suri: &str,
+) -> Result<(<TPublic::Pair as Pair>::Public, String), String> {
+	let (pair, seed) = TPublic::Pair::from_string_with_seed(suri, None)
+		.map_err(|_| "Invalid secret key URI".to_string())?;
+	// Soft derivations leave no seed to store.
+	let seed = seed.ok_or_else(|| "The secret must not end in a soft derivation".to_string())?;
+	Ok((pair.public(), to_hex(seed.as_ref(), false)))
+}
@@ -20,7 +20,7 @@
 
 //! Service implementation. Specialized wrapper over substrate service.
 
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 use sc_consensus_babe;
 use node_primitives::Block;
 use labor_node_runtime::RuntimeApi;
@@ -35,13 +35,33 @@ use sc_client_api::{ExecutorProvider, RemoteBackend};
 use labor_node_executor::Executor;
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sc_consensus_babe::SlotProportion;
+use crate::labor_rpc::{LaborApi, LaborJobs, LaborJobsApi};
+#[cfg(feature = "cli")]
+use crate::{
+	labor_index::{LaborIndex, LaborIndexApi},
+	labor_webhooks::LaborWebhooks,
+};
 
-type FullClient = sc_service::TFullClient<Block, RuntimeApi, Executor>;
+pub(crate) type FullClient = sc_service::TFullClient<Block, RuntimeApi, Executor>;
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 type FullGrandpaBlockImport =
 	grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>;
-type LightClient = sc_service::TLightClient<Block, RuntimeApi, Executor>;
+pub(crate) type LightClient = sc_service::TLightClient<Block, RuntimeApi, Executor>;
+
+/// The optional labor services of a full node.
+#[derive(Debug, Clone, Default)]
+pub struct LaborConfig {
+	/// Where to keep the index of `--labor-index`, if labor events are indexed.
+	pub index_path: Option<PathBuf>,
+	/// The endpoints of `--labor-webhooks`, if labor events are delivered to any.
+	pub webhooks_path: Option<PathBuf>,
+}
+
+/// GRANDPA gossip interval in milliseconds, unless the chain spec says otherwise.
+const GRANDPA_GOSSIP_DURATION: u64 = 333;
+/// Blocks between GRANDPA justifications, unless the chain spec says otherwise.
+const GRANDPA_JUSTIFICATION_PERIOD: u32 = 512;
 
 pub fn new_partial(
 	config: &Configuration,
@@ -149,6 +169,7 @@ pub fn new_partial(
 		let chain_spec = config.chain_spec.cloned_box();
 
 		let rpc_extensions_builder = move |deny_unsafe, subscription_executor| {
+			let labor = crate::labor_rpc::Labor::new(client.clone(), &subscription_executor);
 			let deps = node_rpc::FullDeps {
 				client: client.clone(),
 				pool: pool.clone(),
@@ -169,7 +190,10 @@ pub fn new_partial(
 				},
 			};
 
-			node_rpc::create_full(deps)
+			let mut io = node_rpc::create_full(deps);
+			io.extend_with(LaborApi::to_delegate(labor));
+			io.extend_with(LaborJobsApi::to_delegate(LaborJobs::full(client.clone())));
+			io
 		};
 
 		(rpc_extensions_builder, rpc_setup)
@@ -200,6 +224,7 @@ pub struct NewFullBase {
 /// Creates a full service from the configuration.
 pub fn new_full_base(
 	mut config: Configuration,
+	labor: LaborConfig,
 	with_startup_data: impl FnOnce(
 		&sc_consensus_babe::BabeBlockImport<Block, FullClient, FullGrandpaBlockImport>,
 		&sc_consensus_babe::BabeLink<Block>,
@@ -255,8 +280,38 @@ pub fn new_full_base(
 		Some(sc_consensus_slots::BackoffAuthoringOnFinalizedHeadLagging::default());
 	let name = config.network.node_name.clone();
 	let enable_grandpa = !config.disable_grandpa;
+	let extensions = crate::chain_spec::Extensions::try_get(&*config.chain_spec);
+	let gossip_duration = extensions
+		.and_then(|e| e.grandpa_gossip_duration)
+		.unwrap_or(GRANDPA_GOSSIP_DURATION);
+	let justification_period = extensions
+		.and_then(|e| e.grandpa_justification_period)
+		.unwrap_or(GRANDPA_JUSTIFICATION_PERIOD);
 	let prometheus_registry = config.prometheus_registry().cloned();
 
+	#[cfg(feature = "cli")]
+	let labor_index = labor.index_path.as_deref().map(LaborIndex::open).transpose()?;
+	#[cfg(feature = "cli")]
+	let labor_webhooks = labor
+		.webhooks_path
+		.as_deref()
+		.map(|path| LaborWebhooks::load(path, &config))
+		.transpose()?;
+	#[cfg(feature = "cli")]
+	let rpc_extensions_builder = {
+		let labor_index = labor_index.clone();
+		move |deny_unsafe, subscription_executor| {
+			let mut io = rpc_extensions_builder(deny_unsafe, subscription_executor);
+			if let Some(labor_index) = &labor_index {
+				io.extend_with(LaborIndexApi::to_delegate(labor_index.clone()));
+			}
+			io
+		}
+	};
+	// The labor services keep their data in databases of the CLI build.
+	#[cfg(not(feature = "cli"))]
+	let _ = labor;
+
 	let _rpc_handlers = sc_service::spawn_tasks(
 		sc_service::SpawnTasksParams {
 			config,
@@ -275,6 +330,18 @@ pub fn new_full_base(
 		},
 	)?;
 
+	if let Some(registry) = prometheus_registry.as_ref() {
+		crate::labor_metrics::spawn(&task_manager.spawn_handle(), client.clone(), registry)?;
+	}
+	#[cfg(feature = "cli")]
+	if let Some(labor_index) = labor_index {
+		task_manager.spawn_handle().spawn_blocking("labor-index", labor_index.run(client.clone()));
+	}
+	#[cfg(feature = "cli")]
+	if let Some(labor_webhooks) = labor_webhooks {
+		labor_webhooks.spawn(&task_manager.spawn_handle(), client.clone());
+	}
+
 	let (block_import, grandpa_link, babe_link) = import_setup;
 
 	(with_startup_data)(&block_import, &babe_link);
@@ -345,9 +412,8 @@ pub fn new_full_base(
 	};
 
 	let config = grandpa::Config {
-		// FIXME #1578 make this available through chainspec
-		gossip_duration: std::time::Duration::from_millis(333),
-		justification_period: 512,
+		gossip_duration: std::time::Duration::from_millis(gossip_duration),
+		justification_period,
 		name: Some(name),
 		observer_enabled: false,
 		keystore,
@@ -394,8 +460,9 @@ pub fn new_full_base(
 /// Builds a new service for a full client.
 pub fn new_full(
 	config: Configuration,
+	labor: LaborConfig,
 ) -> Result<TaskManager, ServiceError> {
-	new_full_base(config, |_, _| ()).map(|NewFullBase { task_manager, .. }| {
+	new_full_base(config, labor, |_, _| ()).map(|NewFullBase { task_manager, .. }| {
 		task_manager
 	})
 }
@@ -503,7 +570,12 @@ pub fn new_light_base(
 		pool: transaction_pool.clone(),
 	};
 
-	let rpc_extensions = node_rpc::create_light(light_deps);
+	let mut rpc_extensions = node_rpc::create_light(light_deps);
+	rpc_extensions.extend_with(LaborJobsApi::to_delegate(LaborJobs::light(
+		client.clone(),
+		backend.remote_blockchain(),
+		on_demand.clone(),
+	)));
 
 	let rpc_handlers =
 		sc_service::spawn_tasks(sc_service::SpawnTasksParams {
@@ -598,7 +670,7 @@ mod tests {
 				let mut setup_handles = None;
 				let NewFullBase {
 					task_manager, inherent_data_providers, client, network, transaction_pool, ..
-				} = new_full_base(config,
+				} = new_full_base(config, Default::default(),
 					|
 						block_import: &sc_consensus_babe::BabeBlockImport<Block, _, _>,
 						babe_link: &sc_consensus_babe::BabeLink<Block>,
@@ -773,7 +845,7 @@ mod tests {
 			crate::chain_spec::tests::integration_test_config_with_two_authorities(),
 			|config| {
 				let NewFullBase { task_manager, client, network, transaction_pool, .. }
-					= new_full_base(config,|_, _| ())?;
+					= new_full_base(config, Default::default(), |_, _| ())?;
 				Ok(sc_service_test::TestNetComponents::new(task_manager, client, network, transaction_pool))
 			},
 			|config| {
@@ -0,0 +1,290 @@
+//! Session key commands: offline insertion with `key insert-session`, and rotation with
+//! `session rotate` and `session verify`.
+
+use crate::{
+	labor::{print_json, signed_extrinsic},
+	service::FullClient,
+	InsertSessionCmd, LaborChainParams, SessionCmd, SessionVerifyCmd,
+};
+use codec::{Decode, Encode};
+use frame_support::{Blake2_128Concat, StorageHasher, Twox128, Twox64Concat};
+use labor_node_runtime::{Block, Runtime, SessionKeys};
+use node_primitives::{AccountId, Balance};
+use pallet_staking::StakingLedger;
+use sc_cli::{
+	CliConfiguration, DatabaseParams, Error, KeystoreParams, PruningParams, SharedParams,
+	SubstrateCli,
+};
+use sc_client_api::StorageProvider;
+use sc_keystore::LocalKeystore;
+use sc_service::config::{BasePath, KeystoreConfig};
+use serde::Deserialize;
+use serde_json::json;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	bytes::from_hex,
+	crypto::{key_types, KeyTypeId, Pair, Ss58Codec},
+	ed25519,
+	hexdisplay::HexDisplay,
+	sr25519,
+	storage::StorageKey,
+	Bytes,
+};
+use sp_keystore::SyncCryptoStore;
+use sp_runtime::{generic::BlockId, traits::OpaqueKeys};
+use sp_session::SessionKeys as SessionKeysApi;
+use std::{convert::TryFrom, path::PathBuf, sync::Arc};
+
+/// An `author_insertKey` JSON-RPC request.
+#[derive(Deserialize)]
+struct InsertKeyRequest {
+	method: String,
+	params: (String, String, Bytes),
+}
+
+/// A key read from a key file.
+struct SessionKey {
+	file: PathBuf,
+	key_type: KeyTypeId,
+	suri: String,
+	public: Vec<u8>,
+}
+
+impl InsertSessionCmd {
+	/// Run the command.
+	pub fn run<C: SubstrateCli>(&self, cli: &C) -> Result<(), Error> {
+		let (keys, mut problems) = self.read_keys()?;
+		problems.extend(check(&keys));
+		if !problems.is_empty() {
+			for problem in &problems {
+				eprintln!("{}", problem);
+			}
+			return Err(Error::Input(format!(
+				"The session keys in {} do not match `SessionKeys`",
+				self.dir.display(),
+			)))
+		}
+
+		let base_path = self
+			.shared_params
+			.base_path()
+			.unwrap_or_else(|| BasePath::from_project("", "", &C::executable_name()));
+		let chain_id = self.shared_params.chain_id(self.shared_params.is_dev());
+		let chain_spec = cli.load_spec(&chain_id)?;
+		let config_dir = base_path.config_dir(chain_spec.id());
+		let keystore = match self.keystore_params.keystore_config(&config_dir)? {
+			(_, KeystoreConfig::Path { path, password }) => LocalKeystore::open(path, password)?,
+			_ => unreachable!("keystore_config always returns path and password; qed"),
+		};
+
+		for key in &keys {
+			SyncCryptoStore::insert_unknown(&keystore, key.key_type, &key.suri, &key.public)
+				.map_err(|_| Error::KeyStoreOperation)?;
+			println!("Inserted {} key 0x{}", key_type_name(key.key_type), hex(&key.public));
+		}
+		Ok(())
+	}
+
+	/// Reads the key files of the validator, with the problems of files that are no valid
+	/// `author_insertKey` requests.
+	fn read_keys(&self) -> Result<(Vec<SessionKey>, Vec<String>), Error> {
+		let suffix = self.validator.map(|v| v.to_string()).unwrap_or_default();
+		let mut files = std::fs::read_dir(&self.dir)?
+			.map(|entry| entry.map(|entry| entry.path()))
+			.collect::<Result<Vec<_>, _>>()?;
+		files.retain(|file| {
+			let name = file.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+			name.ends_with(&suffix) &&
+				name.len() > suffix.len() &&
+				name[..name.len() - suffix.len()].chars().all(|c| c.is_ascii_alphabetic()) &&
+				file.is_file()
+		});
+		files.sort();
+
+		let mut keys = Vec::new();
+		let mut problems = Vec::new();
+		for file in files {
+			let request: InsertKeyRequest = match serde_json::from_slice(&std::fs::read(&file)?) {
+				Ok(request) => request,
+				Err(e) => {
+					problems.push(format!("{}: {}", file.display(), e));
+					continue
+				},
+			};
+			if request.method != "author_insertKey" {
+				problems.push(format!("{}: not an `author_insertKey` request", file.display()));
+				continue
+			}
+			let (key_type, suri, public) = request.params;
+			match KeyTypeId::try_from(key_type.as_str()) {
+				Ok(key_type) => keys.push(SessionKey { file, key_type, suri, public: public.0 }),
+				Err(_) => problems.push(format!(
+					"{}: `{}` is no key type, those have four characters",
+					file.display(),
+					key_type,
+				)),
+			}
+		}
+		if keys.is_empty() && problems.is_empty() {
+			return Err(Error::Input(format!("No key files in {}", self.dir.display())))
+		}
+		Ok((keys, problems))
+	}
+}
+
+/// Checks that the keys are exactly the keys of `SessionKeys`, and that every secret key URI
+/// derives its public key.
+fn check(keys: &[SessionKey]) -> Vec<String> {
+	let expected = <SessionKeys as OpaqueKeys>::key_ids();
+	let mut problems = Vec::new();
+	for key in keys {
+		let name = key_type_name(key.key_type);
+		if !expected.contains(&key.key_type) {
+			let expected = expected.iter().map(|&k| key_type_name(k)).collect::<Vec<_>>();
+			problems.push(format!(
+				"{}: `{}` is not a session key type, expected one of {}",
+				key.file.display(),
+				name,
+				expected.join(", "),
+			));
+			continue
+		}
+		if keys.iter().filter(|other| other.key_type == key.key_type).count() > 1 {
+			problems.push(format!("{}: more than one `{}` key", key.file.display(), name));
+		}
+		match derive_public(key.key_type, &key.suri) {
+			Some(public) if public == key.public => {},
+			Some(_) => problems.push(format!(
+				"{}: the secret key URI does not derive the public key 0x{}",
+				key.file.display(),
+				hex(&key.public),
+			)),
+			None => problems.push(format!("{}: invalid secret key URI", key.file.display())),
+		}
+	}
+	for key_type in expected {
+		if !keys.iter().any(|key| key.key_type == *key_type) {
+			problems.push(format!("No `{}` key", key_type_name(*key_type)));
+		}
+	}
+	problems
+}
+
+/// The public key the secret key URI derives in the crypto scheme of the key type.
+fn derive_public(key_type: KeyTypeId, suri: &str) -> Option<Vec<u8>> {
+	if key_type == key_types::GRANDPA {
+		ed25519::Pair::from_string(suri, None).ok().map(|pair| pair.public().0.to_vec())
+	} else {
+		sr25519::Pair::from_string(suri, None).ok().map(|pair| pair.public().0.to_vec())
+	}
+}
+
+fn key_type_name(key_type: KeyTypeId) -> String {
+	String::from_utf8_lossy(&key_type.0).into_owned()
+}
+
+fn hex(bytes: &[u8]) -> String {
+	sp_core::hexdisplay::HexDisplay::from(&bytes).to_string()
+}
+
+impl SessionCmd {
+	/// Run the command against the given client.
+	pub fn run(&self, client: Arc<FullClient>) -> Result<(), Error> {
+		match self {
+			SessionCmd::Rotate(cmd) => {
+				let at = BlockId::Hash(client.info().best_hash);
+				let encoded = client
+					.runtime_api()
+					.generate_session_keys(&at, None)
+					.map_err(|e| Error::Input(format!("Failed to generate keys: {:?}", e)))?;
+				let keys = SessionKeys::decode(&mut &encoded[..])?;
+				// `set_keys` does not check the proof yet.
+				let call = pallet_session::Call::<Runtime>::set_keys(keys, Vec::new());
+				let extrinsic = signed_extrinsic(&client, &cmd.signer, call.into())?;
+				print_json(&json!({
+					"keys": format!("0x{}", HexDisplay::from(&encoded)),
+					"extrinsic": format!("0x{}", HexDisplay::from(&extrinsic.encode())),
+				}))
+			},
+			SessionCmd::Verify(cmd) => verify(&client, cmd),
+		}
+	}
+
+	fn chain(&self) -> &LaborChainParams {
+		match self {
+			SessionCmd::Rotate(cmd) => &cmd.chain,
+			SessionCmd::Verify(cmd) => &cmd.chain,
+		}
+	}
+}
+
+impl CliConfiguration for SessionCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.chain().shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.chain().pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.chain().database_params)
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		match self {
+			SessionCmd::Rotate(cmd) => Some(&cmd.keystore_params),
+			SessionCmd::Verify(_) => None,
+		}
+	}
+}
+
+/// Checks that the next session keys of the controller's stash are the given keys.
+fn verify(client: &FullClient, cmd: &SessionVerifyCmd) -> Result<(), Error> {
+	let controller = AccountId::from_ss58check(&cmd.controller)
+		.map_err(|_| Error::Input(format!("Invalid address `{}`", cmd.controller)))?;
+	let keys = from_hex(&cmd.keys)
+		.ok()
+		.and_then(|keys| SessionKeys::decode(&mut &keys[..]).ok())
+		.ok_or_else(|| Error::Input(format!("Invalid session keys `{}`", cmd.keys)))?;
+
+	let at = BlockId::Hash(client.info().best_hash);
+	let ledger: StakingLedger<AccountId, Balance> =
+		read(client, &at, b"Staking", b"Ledger", &Blake2_128Concat::hash(&controller.encode()))?
+			.ok_or_else(|| Error::Input(format!("{} is not a controller", cmd.controller)))?;
+	let stash = ledger.stash;
+
+	let next: Option<SessionKeys> =
+		read(client, &at, b"Session", b"NextKeys", &Twox64Concat::hash(&stash.encode()))?;
+	if next.as_ref() != Some(&keys) {
+		return Err(Error::Input(format!(
+			"The session keys registered for stash {} are not the given keys",
+			stash,
+		)))
+	}
+
+	let queued: Vec<(AccountId, SessionKeys)> =
+		read(client, &at, b"Session", b"QueuedKeys", &[])?.unwrap_or_default();
+	if queued.contains(&(stash.clone(), keys)) {
+		println!("The session keys of {} are registered and queued for the next session", stash);
+	} else {
+		println!("The session keys of {} are registered, to be queued at the next session", stash);
+	}
+	Ok(())
+}
+
+/// Reads a storage value at `twox128(pallet) ++ twox128(item) ++ suffix`.
+fn read<T: Decode>(
+	client: &FullClient,
+	at: &BlockId<Block>,
+	pallet: &[u8],
+	item: &[u8],
+	suffix: &[u8],
+) -> Result<Option<T>, Error> {
+	let key = [&Twox128::hash(pallet)[..], &Twox128::hash(item)[..], suffix].concat();
+	Ok(client
+		.storage(at, &StorageKey(key))?
+		.map(|data| T::decode(&mut &data.0[..]))
+		.transpose()?)
+}
@@ -0,0 +1,357 @@
+//! The `storage` command: decoding storage items of the local database.
+
+use crate::{
+	labor::print_json,
+	labor_rpc::job_value,
+	service::FullClient,
+	tx::{decode_modules, decoded, generic_arg},
+	StorageCmd,
+};
+use codec::{Compact, Decode};
+use frame_support::metadata::{
+	DecodeDifferent, ModuleMetadata, StorageEntryMetadata, StorageEntryModifier, StorageEntryType,
+	StorageHasher,
+};
+use labor_node_runtime::{Block, Runtime};
+use node_primitives::{AccountId, Hash};
+use pallet_labor::{CompletionOf, Enum, JobOf, JobStatus, RecurringJobOf, StructValue};
+use sc_cli::{CliConfiguration, DatabaseParams, Error, PruningParams, SharedParams};
+use sc_client_api::StorageProvider;
+use serde_json::{json, Value};
+use sp_api::{Metadata, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, hashing::twox_128, hexdisplay::HexDisplay, storage::StorageKey};
+use sp_runtime::generic::BlockId;
+
+impl StorageCmd {
+	/// Run the command against the given client.
+	pub fn run(&self, client: std::sync::Arc<FullClient>) -> Result<(), Error> {
+		let mut parts = self.item.splitn(2, "::");
+		let (pallet, item) = match (parts.next(), parts.next()) {
+			(Some(pallet), Some(item)) => (pallet, item),
+			_ => return Err(Error::Input("Expected the storage item as `Pallet::Item`".into())),
+		};
+		let at = match &self.at {
+			Some(at) => at.parse::<Block>().map_err(Error::Input)?,
+			None => BlockId::Hash(client.info().best_hash),
+		};
+		// The runtime at the block may differ from the native one.
+		let metadata = client
+			.runtime_api()
+			.metadata(&at)
+			.map_err(|e| Error::Input(format!("Failed to read the metadata: {:?}", e)))?;
+		let (storage_prefix, entry) = entry(&decode_modules(&metadata)?, pallet, item)?;
+
+		let mut prefix = twox_128(storage_prefix.as_bytes()).to_vec();
+		prefix.extend(twox_128(item.as_bytes()));
+
+		let (keys, value_name) = match &entry.ty {
+			StorageEntryType::Plain(value) => {
+				let value_name = decoded(value).unwrap_or_default();
+				let value_ty = supported(pallet, item, &value_name)?;
+				let data = match client.storage(&at, &StorageKey(prefix))? {
+					Some(data) => data.0,
+					// Optional items have no value until they are set.
+					None if matches!(entry.modifier, StorageEntryModifier::Optional) => {
+						return print_json(&Value::Null)
+					},
+					None => match &entry.default {
+						DecodeDifferent::Decoded(default) => default.clone(),
+						DecodeDifferent::Encode(_) => Vec::new(),
+					},
+				};
+				return print_json(&decode_value(&value_ty, &value_name, &data))
+			},
+			StorageEntryType::Map { hasher, key, value, .. } => {
+				(vec![(hasher.clone(), decoded(key).unwrap_or_default())], value)
+			},
+			StorageEntryType::DoubleMap { hasher, key1, key2, value, key2_hasher } => (
+				vec![
+					(hasher.clone(), decoded(key1).unwrap_or_default()),
+					(key2_hasher.clone(), decoded(key2).unwrap_or_default()),
+				],
+				value,
+			),
+			StorageEntryType::NMap { keys, hashers, value } => {
+				(decoded_array(hashers).into_iter().zip(decoded_array(keys)).collect(), value)
+			},
+		};
+		let value_name = decoded(value_name).unwrap_or_default();
+		let value_ty = supported(pallet, item, &value_name)?;
+		let keys = keys
+			.into_iter()
+			.map(|(hasher, key)| (hasher, parse(&key).ok()))
+			.collect::<Vec<_>>();
+
+		let entries = client
+			.storage_pairs(&at, &StorageKey(prefix.clone()))?
+			.into_iter()
+			.map(|(key, value)| {
+				json!({
+					"key": decode_keys(&keys, &key.0[prefix.len()..]),
+					"value": decode_value(&value_ty, &value_name, &value.0),
+				})
+			})
+			.collect::<Vec<_>>();
+		print_json(&json!(entries))
+	}
+}
+
+impl CliConfiguration for StorageCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+/// The type of the values of `pallet::item`, if the command can decode it.
+fn supported(pallet: &str, item: &str, value: &str) -> Result<Ty, Error> {
+	parse(value).map_err(|ty| {
+		Error::Input(format!(
+			"`{}::{}` holds values of type `{}`, which cannot be decoded: only the items of the \
+			labor pallet and items of primitive types are supported",
+			pallet, item, ty,
+		))
+	})
+}
+
+/// The storage prefix of `pallet` and the metadata of its storage item `item`.
+fn entry(
+	modules: &[ModuleMetadata],
+	pallet: &str,
+	item: &str,
+) -> Result<(String, StorageEntryMetadata), Error> {
+	let module = modules
+		.iter()
+		.find(|module| decoded(&module.name).map_or(false, |n| n == pallet))
+		.ok_or_else(|| Error::Input(format!("Unknown pallet `{}`", pallet)))?;
+	let (prefix, entries) = match &module.storage {
+		Some(DecodeDifferent::Decoded(storage)) => {
+			(decoded(&storage.prefix).unwrap_or_default(), decoded_array(&storage.entries))
+		},
+		_ => (String::new(), Vec::new()),
+	};
+	let entry = entries
+		.into_iter()
+		.find(|entry| decoded(&entry.name).map_or(false, |n| n == item))
+		.ok_or_else(|| Error::Input(format!("Unknown storage item `{}::{}`", pallet, item)))?;
+	Ok((prefix, entry))
+}
+
+fn decoded_array<B, O: Clone>(value: &DecodeDifferent<B, Vec<O>>) -> Vec<O> {
+	match value {
+		DecodeDifferent::Decoded(value) => value.clone(),
+		DecodeDifferent::Encode(_) => Vec::new(),
+	}
+}
+
+/// Decode the keys that follow the item prefix of a storage key.
+///
+/// Keys are only recoverable behind concatenating hashers; opaque hashes are printed as hex. Once
+/// a key cannot be decoded, or its type is not supported, the rest of the storage key is printed
+/// as hex.
+fn decode_keys(keys: &[(StorageHasher, Option<Ty>)], mut data: &[u8]) -> Vec<Value> {
+	let mut decoded = Vec::with_capacity(keys.len());
+	for (hasher, ty) in keys {
+		let (hash_len, concat) = match hasher {
+			StorageHasher::Blake2_128 | StorageHasher::Twox128 => (16, false),
+			StorageHasher::Blake2_256 | StorageHasher::Twox256 => (32, false),
+			StorageHasher::Blake2_128Concat => (16, true),
+			StorageHasher::Twox64Concat => (8, true),
+			StorageHasher::Identity => (0, true),
+		};
+		if data.len() < hash_len {
+			break
+		}
+		let (hash, rest) = data.split_at(hash_len);
+		data = rest;
+		if !concat {
+			decoded.push(hex(hash));
+			continue
+		}
+		match ty.as_ref().map(|ty| decode(ty, &mut data)) {
+			Some(Ok(key)) => decoded.push(key),
+			_ => break,
+		}
+	}
+	if !data.is_empty() {
+		decoded.push(hex(data));
+	}
+	decoded
+}
+
+/// Decode a value, falling back to hex if the data does not match its type.
+fn decode_value(ty: &Ty, name: &str, data: &[u8]) -> Value {
+	let mut input = data;
+	match decode(ty, &mut input) {
+		Ok(value) if input.is_empty() => value,
+		_ => json!({ "type": name, "raw": hex(data) }),
+	}
+}
+
+/// The types the command decodes: those of the labor pallet items and primitives.
+///
+/// The metadata only names types, so the composite types of the labor pallet are decoded by name
+/// with their native definitions. Any other composite type is not supported.
+enum Ty {
+	Unit,
+	Bool,
+	U8,
+	U16,
+	U32,
+	U64,
+	/// Balances and other `u128`s, printed as strings; they do not fit into a JSON number.
+	U128,
+	AccountId,
+	Hash,
+	Bytes,
+	Compact(Box<Ty>),
+	Option(Box<Ty>),
+	Vec(Box<Ty>),
+	Tuple(Vec<Ty>),
+	Job,
+	RecurringJob,
+	Completion,
+	JobStatus,
+	StructValue,
+	Enum,
+}
+
+/// The type named `ty` in the metadata, or the name of the part of it that is not supported.
+fn parse(ty: &str) -> Result<Ty, String> {
+	let ty: String = ty.chars().filter(|c| !c.is_whitespace()).collect();
+	parse_ty(&ty)
+}
+
+fn parse_ty(ty: &str) -> Result<Ty, String> {
+	if let Some(inner) = generic_arg(ty, "Compact") {
+		return match parse_ty(inner)? {
+			inner @ Ty::U8 | inner @ Ty::U16 | inner @ Ty::U32 | inner @ Ty::U64 |
+			inner @ Ty::U128 => Ok(Ty::Compact(Box::new(inner))),
+			_ => Err(ty.into()),
+		}
+	}
+	if let Some(inner) = generic_arg(ty, "Option") {
+		return Ok(Ty::Option(Box::new(parse_ty(inner)?)))
+	}
+	if ty == "Vec<u8>" {
+		return Ok(Ty::Bytes)
+	}
+	if let Some(inner) = generic_arg(ty, "Vec") {
+		return Ok(Ty::Vec(Box::new(parse_ty(inner)?)))
+	}
+	if ty == "()" {
+		return Ok(Ty::Unit)
+	}
+	if let Some(items) = ty.strip_prefix('(').and_then(|ty| ty.strip_suffix(')')) {
+		return tuple_items(items).into_iter().map(parse_ty).collect::<Result<_, _>>().map(Ty::Tuple)
+	}
+
+	Ok(match ty {
+		"bool" => Ty::Bool,
+		"u8" => Ty::U8,
+		"u16" => Ty::U16,
+		"u32" | "T::BlockNumber" | "T::Index" | "JobId" | "SeriesId" => Ty::U32,
+		"u64" | "T::Moment" => Ty::U64,
+		"u128" | "Balance" | "T::Balance" | "BalanceOf<T>" => Ty::U128,
+		"AccountId" | "T::AccountId" => Ty::AccountId,
+		"Hash" | "T::Hash" | "H256" => Ty::Hash,
+		"JobOf<T>" => Ty::Job,
+		"RecurringJobOf<T>" => Ty::RecurringJob,
+		"CompletionOf<T>" => Ty::Completion,
+		"JobStatus" => Ty::JobStatus,
+		"StructValue" => Ty::StructValue,
+		"Enum" => Ty::Enum,
+		_ => return Err(ty.into()),
+	})
+}
+
+/// The comma separated types of a tuple, without the parentheses.
+fn tuple_items(items: &str) -> Vec<&str> {
+	let (mut depth, mut start, mut parts) = (0, 0, Vec::new());
+	for (i, c) in items.char_indices() {
+		match c {
+			'<' | '(' | '[' => depth += 1,
+			'>' | ')' | ']' => depth -= 1,
+			',' if depth == 0 => {
+				parts.push(&items[start..i]);
+				start = i + 1;
+			},
+			_ => {},
+		}
+	}
+	if start < items.len() {
+		parts.push(&items[start..]);
+	}
+	parts
+}
+
+/// Decode a value of type `ty` from `input`, advancing it past the decoded data.
+fn decode(ty: &Ty, input: &mut &[u8]) -> Result<Value, codec::Error> {
+	let value = match ty {
+		Ty::Unit => Value::Null,
+		Ty::Bool => json!(bool::decode(input)?),
+		Ty::U8 => json!(u8::decode(input)?),
+		Ty::U16 => json!(u16::decode(input)?),
+		Ty::U32 => json!(u32::decode(input)?),
+		Ty::U64 => json!(u64::decode(input)?),
+		Ty::U128 => json!(u128::decode(input)?.to_string()),
+		Ty::AccountId => json!(AccountId::decode(input)?.to_ss58check()),
+		Ty::Hash => hex(Hash::decode(input)?.as_ref()),
+		Ty::Bytes => hex(&Vec::<u8>::decode(input)?),
+		// Only integers are compact, and a compact value fits into the integer it encodes.
+		Ty::Compact(inner) => match (inner.as_ref(), Compact::<u128>::decode(input)?.0) {
+			(Ty::U128, value) => json!(value.to_string()),
+			(_, value) => json!(value as u64),
+		},
+		Ty::Option(inner) => match u8::decode(input)? {
+			0 => Value::Null,
+			1 => decode(inner, input)?,
+			_ => return Err("invalid option".into()),
+		},
+		Ty::Vec(inner) => {
+			let len = Compact::<u32>::decode(input)?.0;
+			Value::Array((0..len).map(|_| decode(inner, input)).collect::<Result<_, _>>()?)
+		},
+		Ty::Tuple(items) => {
+			Value::Array(items.iter().map(|item| decode(item, input)).collect::<Result<_, _>>()?)
+		},
+		Ty::Job => job_value(&JobOf::<Runtime>::decode(input)?),
+		Ty::RecurringJob => {
+			let job = RecurringJobOf::<Runtime>::decode(input)?;
+			json!({
+				"employer": job.employer.to_ss58check(),
+				"details": String::from_utf8_lossy(&job.details),
+				"payment": job.payment.to_string(),
+				"workerBond": job.worker_bond.to_string(),
+				"period": job.period,
+			})
+		},
+		Ty::Completion => {
+			let completion = CompletionOf::<Runtime>::decode(input)?;
+			json!({
+				"job": completion.job,
+				"worker": completion.worker.to_ss58check(),
+				"payment": completion.payment.to_string(),
+			})
+		},
+		Ty::JobStatus => json!(format!("{:?}", JobStatus::decode(input)?)),
+		Ty::StructValue => {
+			let value = StructValue::decode(input)?;
+			json!({ "number": value.number, "string": String::from_utf8_lossy(&value.string) })
+		},
+		Ty::Enum => json!(format!("{:?}", Enum::decode(input)?)),
+	};
+	Ok(value)
+}
+
+fn hex(data: &[u8]) -> Value {
+	json!(format!("0x{}", HexDisplay::from(&data)))
+}
@@ -0,0 +1,288 @@
+//! The `tx` command: offline construction and signing of extrinsics.
+
+use crate::{
+	extrinsic::{create_extrinsic, SigningContext},
+	TxCmd,
+};
+use codec::{Compact, Decode, Encode};
+use frame_support::metadata::{
+	DecodeDifferent, FunctionMetadata, ModuleMetadata, RuntimeMetadata, RuntimeMetadataPrefixed,
+};
+use labor_node_executor::Executor;
+use labor_node_runtime::{Address, Block, Call, Runtime};
+use node_primitives::{AccountId, Hash};
+use sc_cli::{utils, CliConfiguration, Error, SharedParams};
+use sc_executor::{NativeExecutor, RuntimeInfo, WasmExecutionMethod};
+use serde_json::Value;
+use sp_core::{
+	bytes::from_hex,
+	crypto::{Pair, Ss58Codec},
+	hashing::blake2_256,
+	hexdisplay::HexDisplay,
+	sr25519,
+	storage::well_known_keys,
+	traits::{RuntimeCode, WrappedRuntimeCode},
+};
+use sp_runtime::{
+	generic::Era,
+	traits::{Block as BlockT, Hash as HashT, Header as HeaderT},
+	BuildStorage,
+};
+use sp_state_machine::{BasicExternalities, Externalities};
+use std::convert::TryFrom;
+
+impl TxCmd {
+	/// Run the command for the given chain spec.
+	pub fn run(&self, chain_spec: Box<dyn sc_service::ChainSpec>) -> Result<(), Error> {
+		let json = match &self.call {
+			Some(call) => call.clone().into_bytes(),
+			None => utils::read_message(None, false)?,
+		};
+		let json: Value = serde_json::from_slice(&json)
+			.map_err(|e| Error::Input(format!("Invalid call JSON: {}", e)))?;
+		let call = encode_call(&json)?;
+
+		let suri = utils::read_uri(self.suri.as_ref())?;
+		let signer = sr25519::Pair::from_string(&suri, None)
+			.map_err(|_| Error::Input("Invalid secret key URI".into()))?;
+
+		let context = self.signing_context(chain_spec)?;
+		let era = match self.mortality {
+			Some(period) => Era::mortal(period, self.era_block_number.unwrap_or_default()),
+			None => Era::Immortal,
+		};
+		let era_block_hash = match &self.era_block_hash {
+			Some(hash) => decode_fixed(&Value::String(hash.clone())).map_err(Error::Input)?.into(),
+			None => context.genesis_hash,
+		};
+		let context = SigningContext { era_block_hash, ..context };
+
+		let extrinsic = create_extrinsic(&signer, call, self.nonce, self.tip, era, &context);
+		println!("0x{}", HexDisplay::from(&extrinsic.encode()));
+		Ok(())
+	}
+
+	/// Derive the genesis hash and the runtime version from the genesis state of the chain spec.
+	fn signing_context(
+		&self,
+		chain_spec: Box<dyn sc_service::ChainSpec>,
+	) -> Result<SigningContext, Error> {
+		let storage = chain_spec.build_storage().map_err(Error::Input)?;
+		let code = storage
+			.top
+			.get(well_known_keys::CODE)
+			.cloned()
+			.ok_or_else(|| Error::Input("The chain spec has no runtime code".into()))?;
+		let mut ext = BasicExternalities::new(storage);
+
+		let state_root = Hash::decode(&mut &ext.storage_root()[..])?;
+		let extrinsics_root =
+			<<<Block as BlockT>::Header as HeaderT>::Hashing as HashT>::trie_root(Vec::new());
+		let genesis = <Block as BlockT>::Header::new(
+			0,
+			extrinsics_root,
+			state_root,
+			Default::default(),
+			Default::default(),
+		);
+
+		let executor = NativeExecutor::<Executor>::new(WasmExecutionMethod::Interpreted, None, 8);
+		let runtime_code = RuntimeCode {
+			code_fetcher: &WrappedRuntimeCode(code.as_slice().into()),
+			heap_pages: None,
+			hash: blake2_256(&code).to_vec(),
+		};
+		let version = executor
+			.runtime_version(&mut ext, &runtime_code)
+			.map_err(|e| Error::Input(format!("Failed to read the runtime version: {}", e)))?;
+
+		Ok(SigningContext {
+			genesis_hash: genesis.hash(),
+			era_block_hash: genesis.hash(),
+			spec_version: version.spec_version,
+			transaction_version: version.transaction_version,
+		})
+	}
+}
+
+impl CliConfiguration for TxCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+/// Encode a call described as `{"pallet": .., "call": .., "args": ..}` using the runtime metadata.
+fn encode_call(json: &Value) -> Result<Call, Error> {
+	let pallet = json["pallet"]
+		.as_str()
+		.ok_or_else(|| Error::Input("Missing `pallet`".into()))?;
+	let name = json["call"]
+		.as_str()
+		.ok_or_else(|| Error::Input("Missing `call`".into()))?;
+
+	let module = modules()?
+		.into_iter()
+		.find(|module| decoded(&module.name).map_or(false, |n| n == pallet))
+		.ok_or_else(|| Error::Input(format!("Unknown pallet `{}`", pallet)))?;
+	let calls: Vec<FunctionMetadata> = match module.calls {
+		Some(DecodeDifferent::Decoded(calls)) => calls,
+		_ => Vec::new(),
+	};
+	let (call_index, function) = calls
+		.iter()
+		.enumerate()
+		.find(|(_, function)| decoded(&function.name).map_or(false, |n| n == name))
+		.ok_or_else(|| Error::Input(format!("Unknown call `{}::{}`", pallet, name)))?;
+
+	let mut encoded = vec![module.index, call_index as u8];
+	let arguments = match &function.arguments {
+		DecodeDifferent::Decoded(arguments) => arguments.clone(),
+		DecodeDifferent::Encode(_) => Vec::new(),
+	};
+	for (position, argument) in arguments.iter().enumerate() {
+		let arg_name = decoded(&argument.name).unwrap_or_default();
+		let ty = decoded(&argument.ty).unwrap_or_default();
+		let value = match &json["args"] {
+			Value::Array(values) => values.get(position),
+			Value::Object(values) => values.get(&arg_name),
+			_ => None,
+		}
+		.ok_or_else(|| Error::Input(format!("Missing argument `{}`", arg_name)))?;
+
+		encode_arg(&ty, value, &mut encoded)
+			.map_err(|e| Error::Input(format!("Argument `{}` ({}): {}", arg_name, ty, e)))?;
+	}
+
+	Call::decode(&mut &encoded[..])
+		.map_err(|e| Error::Input(format!("Arguments do not form a valid call: {}", e)))
+}
+
+/// The pallets of the native runtime, with their metadata in decoded form.
+fn modules() -> Result<Vec<ModuleMetadata>, Error> {
+	// Round-trip through SCALE so that all `DecodeDifferent` fields are `Decoded`. Some constants
+	// are read from storage, which is empty here: those show the runtime defaults.
+	let metadata = BasicExternalities::new_empty().execute_with(Runtime::metadata);
+	decode_modules(&metadata.encode())
+}
+
+/// The pallets described by SCALE-encoded runtime metadata.
+pub(crate) fn decode_modules(metadata: &[u8]) -> Result<Vec<ModuleMetadata>, Error> {
+	let metadata = RuntimeMetadataPrefixed::decode(&mut &metadata[..])?;
+	match metadata.1 {
+		RuntimeMetadata::V13(metadata) => match metadata.modules {
+			DecodeDifferent::Decoded(modules) => Ok(modules),
+			DecodeDifferent::Encode(_) => Ok(Vec::new()),
+		},
+		_ => Err(Error::Input("Unsupported metadata version".into())),
+	}
+}
+
+pub(crate) fn decoded<B>(value: &DecodeDifferent<B, String>) -> Option<String> {
+	match value {
+		DecodeDifferent::Decoded(value) => Some(value.clone()),
+		DecodeDifferent::Encode(_) => None,
+	}
+}
+
+/// SCALE-encode a JSON value as the metadata type `ty`.
+fn encode_arg(ty: &str, value: &Value, out: &mut Vec<u8>) -> Result<(), String> {
+	if let Some(scale) = value.get("scale").and_then(Value::as_str) {
+		out.extend(from_hex(scale).map_err(|_| "invalid hex".to_string())?);
+		return Ok(())
+	}
+
+	let ty: String = ty.chars().filter(|c| !c.is_whitespace()).collect();
+	if let Some(inner) = generic_arg(&ty, "Compact") {
+		return encode_compact(inner, value, out)
+	}
+	if let Some(inner) = generic_arg(&ty, "Option") {
+		if value.is_null() {
+			out.push(0);
+			return Ok(())
+		}
+		out.push(1);
+		return encode_arg(inner, value, out)
+	}
+	if ty == "Vec<u8>" {
+		bytes(value)?.encode_to(out);
+		return Ok(())
+	}
+	if let Some(inner) = generic_arg(&ty, "Vec") {
+		let values = value.as_array().ok_or("expected an array")?;
+		Compact(values.len() as u32).encode_to(out);
+		return values.iter().try_for_each(|value| encode_arg(inner, value, out))
+	}
+
+	match ty.as_str() {
+		"bool" => value.as_bool().ok_or("expected a boolean")?.encode_to(out),
+		"u8" => u8::try_from(number(value)?).map_err(|e| e.to_string())?.encode_to(out),
+		"u16" => u16::try_from(number(value)?).map_err(|e| e.to_string())?.encode_to(out),
+		"u64" | "T::Moment" => {
+			u64::try_from(number(value)?).map_err(|e| e.to_string())?.encode_to(out)
+		},
+		"u128" => number(value)?.encode_to(out),
+		"<T::LookupasStaticLookup>::Source" => Address::Id(account(value)?).encode_to(out),
+		ty if ty.ends_with("AccountId") => account(value)?.encode_to(out),
+		ty if ty.ends_with("Hash") || ty == "H256" => decode_fixed(value)?.encode_to(out),
+		ty if ty.contains("Balance") => number(value)?.encode_to(out),
+		"u32" | "T::BlockNumber" | "T::Index" | "JobId" | "SeriesId" => {
+			u32::try_from(number(value)?).map_err(|e| e.to_string())?.encode_to(out)
+		},
+		_ => return Err("unsupported type, pass it pre-encoded as {\"scale\": \"0x..\"}".into()),
+	}
+	Ok(())
+}
+
+fn encode_compact(ty: &str, value: &Value, out: &mut Vec<u8>) -> Result<(), String> {
+	let value = number(value)?;
+	match ty {
+		"u32" | "T::BlockNumber" | "T::Index" | "JobId" | "SeriesId" => {
+			Compact(u32::try_from(value).map_err(|e| e.to_string())?).encode_to(out)
+		},
+		"u64" | "T::Moment" => {
+			Compact(u64::try_from(value).map_err(|e| e.to_string())?).encode_to(out)
+		},
+		_ => Compact(value).encode_to(out),
+	}
+	Ok(())
+}
+
+/// The argument of a single-parameter generic type such as `Vec<T>`.
+pub(crate) fn generic_arg<'a>(ty: &'a str, name: &str) -> Option<&'a str> {
+	ty.strip_prefix(name)?.strip_prefix('<')?.strip_suffix('>')
+}
+
+/// Numbers may be given as JSON numbers or, for values beyond `u64`, as strings.
+fn number(value: &Value) -> Result<u128, String> {
+	match value {
+		Value::Number(n) => {
+			n.as_u64().map(Into::into).ok_or_else(|| "expected an unsigned integer".into())
+		},
+		Value::String(s) => s.parse().map_err(|_| "expected an unsigned integer".into()),
+		_ => Err("expected an unsigned integer".into()),
+	}
+}
+
+/// Bytes may be given as `0x`-prefixed hex or as UTF-8 text.
+fn bytes(value: &Value) -> Result<Vec<u8>, String> {
+	let text = value.as_str().ok_or("expected a string")?;
+	if text.starts_with("0x") {
+		from_hex(text).map_err(|_| "invalid hex".into())
+	} else {
+		Ok(text.as_bytes().to_vec())
+	}
+}
+
+fn account(value: &Value) -> Result<AccountId, String> {
+	let text = value.as_str().ok_or("expected an SS58 address or hex public key")?;
+	if text.starts_with("0x") {
+		Ok(decode_fixed(value)?.into())
+	} else {
+		AccountId::from_ss58check(text).map_err(|_| "invalid SS58 address".into())
+	}
+}
+
+fn decode_fixed(value: &Value) -> Result<[u8; 32], String> {
+	let bytes = bytes(value)?;
+	<[u8; 32]>::try_from(bytes.as_slice()).map_err(|_| "expected 32 bytes".into())
+}
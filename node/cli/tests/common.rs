@@ -19,6 +19,7 @@
 #![cfg(unix)]
 
 use std::{process::{Child, ExitStatus}, thread, time::Duration, path::Path};
+use std::{io::{Read, Write}, net::TcpStream};
 use assert_cmd::cargo::cargo_bin;
 use std::{convert::TryInto, process::Command};
 use nix::sys::signal::{kill, Signal::SIGINT};
@@ -65,3 +66,66 @@ pub fn run_dev_node_for_a_while(base_path: &Path) {
 	kill(Pid::from_raw(cmd.id().try_into().unwrap()), SIGINT).unwrap();
 	assert!(wait_for(&mut cmd, 40).map(|x| x.success()).unwrap_or_default());
 }
+
+/// Call `method` of the HTTP RPC server of a node at `port`, retrying while the server starts.
+///
+/// Returns the raw HTTP response.
+pub fn rpc(port: u16, method: &str, params: serde_json::Value) -> String {
+	let body = serde_json::json!({
+		"jsonrpc": "2.0",
+		"id": 1,
+		"method": method,
+		"params": params,
+	})
+	.to_string();
+	for _ in 0..30 {
+		if let Ok(mut stream) = TcpStream::connect(("127.0.0.1", port)) {
+			write!(
+				stream,
+				"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+				Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+				body.len(),
+				body,
+			)
+			.unwrap();
+			let mut response = String::new();
+			stream.read_to_string(&mut response).unwrap();
+			return response
+		}
+		thread::sleep(Duration::from_secs(1));
+	}
+	panic!("the RPC server did not start");
+}
+
+/// Sign a job of "Paint the fence" for a payment of 1000 as Alice, with the nonce read from the
+/// database at `base_path`.
+///
+/// Returns the hex encoded extrinsic.
+pub fn post_job(base_path: &Path) -> String {
+	let output = Command::new(cargo_bin("labor-node"))
+		.args(&["labor", "post", "--dev", "--suri", "//Alice", "--details", "Paint the fence"])
+		.args(&["--payment", "1000", "-d"])
+		.arg(base_path)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	String::from_utf8(output.stdout).unwrap().trim().into()
+}
+
+/// Start a dev node that seals a block for every extrinsic, with the database at `base_path`
+/// and `args` added to its command line.
+pub fn start_node(base_path: &Path, args: &[&str]) -> Child {
+	Command::new(cargo_bin("labor-node"))
+		.args(&["--dev", "--sealing", "instant"])
+		.args(args)
+		.arg("-d")
+		.arg(base_path)
+		.spawn()
+		.unwrap()
+}
+
+/// Interrupt the node and wait until it exited gracefully.
+pub fn stop_node(mut node: Child) {
+	kill(Pid::from_raw(node.id().try_into().unwrap()), SIGINT).unwrap();
+	assert!(wait_for(&mut node, 40).map(|x| x.success()).unwrap_or_default());
+}
@@ -0,0 +1,79 @@
+use assert_cmd::cargo::cargo_bin;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn generate_spec_builds_raw_spec_from_toml() {
+	let dir = tempdir().expect("could not create a temp dir");
+	let output = dir.path().join("testnet.json");
+
+	let status = Command::new(cargo_bin("labor-node"))
+		.arg("generate-spec")
+		.arg(concat!(env!("CARGO_MANIFEST_DIR"), "/res/testnet.toml"))
+		.arg("--output")
+		.arg(&output)
+		.status()
+		.unwrap();
+	assert!(status.success());
+
+	let spec: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+	assert_eq!(spec["id"], "labor-testnet");
+	assert_eq!(spec["properties"]["tokenSymbol"], "LBR");
+	assert!(spec["genesis"]["raw"]["top"].as_object().map_or(false, |top| !top.is_empty()));
+}
+
+#[test]
+fn generate_spec_accepts_json_and_rejects_incomplete_authorities() {
+	let dir = tempdir().expect("could not create a temp dir");
+	let input = dir.path().join("net.json");
+	std::fs::write(
+		&input,
+		r#"{"name": "Net", "id": "net", "sudo": "//Alice", "authorities": [{"stash": "//Bob"}]}"#,
+	)
+	.unwrap();
+
+	let output = Command::new(cargo_bin("labor-node"))
+		.arg("generate-spec")
+		.arg(&input)
+		.output()
+		.unwrap();
+	assert!(!output.status.success());
+	assert!(String::from_utf8_lossy(&output.stderr).contains("controller"));
+}
+
+#[test]
+fn generate_spec_leaves_out_the_sudo_key_with_no_sudo() {
+	const SUDO_KEY: &str = "0x5c0d1176a568c1f92944340dbfed9e9c530ebca703c85910e7164cb7d1c9e47b";
+
+	let dir = tempdir().expect("could not create a temp dir");
+	let input = dir.path().join("net.json");
+	std::fs::write(&input, r#"{"name": "Net", "id": "net", "authorities": [{"suri": "//Alice"}]}"#)
+		.unwrap();
+
+	let output = Command::new(cargo_bin("labor-node"))
+		.arg("generate-spec")
+		.arg(&input)
+		.output()
+		.unwrap();
+	assert!(!output.status.success());
+	assert!(String::from_utf8_lossy(&output.stderr).contains("--no-sudo"));
+
+	let output = Command::new(cargo_bin("labor-node"))
+		.arg("generate-spec")
+		.arg(&input)
+		.arg("--no-sudo")
+		.output()
+		.unwrap();
+	// The genesis of the sudo pallet always sets a key.
+	if cfg!(feature = "sudo") {
+		assert!(!output.status.success());
+		assert!(String::from_utf8_lossy(&output.stderr).contains("`sudo` feature"));
+		return
+	}
+	assert!(output.status.success());
+	let spec: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	let top = spec["genesis"]["raw"]["top"].as_object().unwrap();
+	assert!(!top.is_empty());
+	assert!(!top.contains_key(SUDO_KEY));
+}
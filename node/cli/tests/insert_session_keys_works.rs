@@ -0,0 +1,68 @@
+use assert_cmd::cargo::cargo_bin;
+use sp_core::{crypto::Pair, ed25519, hexdisplay::HexDisplay, sr25519};
+use std::{path::Path, process::Command};
+use tempfile::tempdir;
+
+fn write_key_file(dir: &Path, name: &str, key_type: &str, suri: &str, public: &[u8]) {
+	let request = serde_json::json!({
+		"jsonrpc": "2.0",
+		"id": 1,
+		"method": "author_insertKey",
+		"params": [key_type, suri, format!("0x{}", HexDisplay::from(&public))],
+	});
+	std::fs::write(dir.join(name), request.to_string()).unwrap();
+}
+
+fn write_session_keys(dir: &Path, im_online_key_type: &str) {
+	let sr25519 = |suri: &str| sr25519::Pair::from_string(suri, None).unwrap().public().0;
+	let ed25519 = |suri: &str| ed25519::Pair::from_string(suri, None).unwrap().public().0;
+	write_key_file(dir, "gran1", "gran", "//Alice//grandpa", &ed25519("//Alice//grandpa"));
+	write_key_file(dir, "babe1", "babe", "//Alice//babe", &sr25519("//Alice//babe"));
+	write_key_file(dir, "imol1", im_online_key_type, "//Alice//imon", &sr25519("//Alice//imon"));
+	write_key_file(dir, "audi1", "audi", "//Alice//audi", &sr25519("//Alice//audi"));
+	// Keys of another validator are not read.
+	write_key_file(dir, "babe2", "babe", "//Bob//babe", &sr25519("//Bob//babe"));
+}
+
+#[test]
+fn insert_session_inserts_all_session_keys() {
+	let dir = tempdir().expect("could not create a temp dir");
+	let keys = dir.path().join("keys");
+	std::fs::create_dir(&keys).unwrap();
+	write_session_keys(&keys, "imon");
+	let base_path = dir.path().join("node");
+
+	let status = Command::new(cargo_bin("labor-node"))
+		.args(&["key", "insert-session", "--dev", "--validator", "1", "--dir"])
+		.arg(&keys)
+		.arg("--base-path")
+		.arg(&base_path)
+		.status()
+		.unwrap();
+	assert!(status.success());
+
+	let keystore = std::fs::read_dir(base_path.join("chains/dev/keystore")).unwrap();
+	assert_eq!(keystore.count(), 4);
+}
+
+#[test]
+fn insert_session_reports_mismatching_key_types() {
+	let dir = tempdir().expect("could not create a temp dir");
+	let keys = dir.path().join("keys");
+	std::fs::create_dir(&keys).unwrap();
+	write_session_keys(&keys, "imol");
+	let base_path = dir.path().join("node");
+
+	let output = Command::new(cargo_bin("labor-node"))
+		.args(&["key", "insert-session", "--dev", "--validator", "1", "--dir"])
+		.arg(&keys)
+		.arg("--base-path")
+		.arg(&base_path)
+		.output()
+		.unwrap();
+	assert!(!output.status.success());
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(stderr.contains("`imol` is not a session key type"));
+	assert!(stderr.contains("No `imon` key"));
+	assert!(!base_path.join("chains/dev/keystore").exists());
+}
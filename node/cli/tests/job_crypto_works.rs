@@ -0,0 +1,57 @@
+use assert_cmd::cargo::cargo_bin;
+use std::process::Command;
+
+fn job_crypto(args: &[&str]) -> String {
+	let output = Command::new(cargo_bin("labor-node"))
+		.arg("job-crypto")
+		.args(args)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	String::from_utf8(output.stdout).unwrap()
+}
+
+fn field<'a>(output: &'a str, label: &str) -> &'a str {
+	output
+		.lines()
+		.find_map(|line| line.strip_prefix(label))
+		.map(str::trim)
+		.expect("label is printed")
+}
+
+#[test]
+fn job_crypto_round_trip_works() {
+	let encrypted = job_crypto(&["encrypt", "--message", "night shift, 25/h"]);
+	let key = field(&encrypted, "Content key:");
+	let payload = field(&encrypted, "Payload:");
+
+	let decrypted = job_crypto(&["decrypt", "--key", key, "--message", payload]);
+	assert_eq!(decrypted.trim(), "night shift, 25/h");
+
+	// Alice's well-known dev account.
+	let sealed = job_crypto(&[
+		"seal-key", "--key", key, "--worker", "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+	]);
+	let opened = job_crypto(&["open-key", "--sealed", sealed.trim(), "--suri", "//Alice"]);
+	assert_eq!(opened.trim(), key);
+}
+
+#[test]
+fn content_keys_are_sealed_to_x25519_keys() {
+	let encrypted = job_crypto(&["encrypt", "--message", "night shift, 25/h"]);
+	let key = field(&encrypted, "Content key:");
+
+	let worker = job_crypto(&["x25519-key", "--suri", "//Bob"]);
+	let sealed = job_crypto(&["seal-key", "--x25519", "--key", key, "--worker", worker.trim()]);
+	let opened =
+		job_crypto(&["open-key", "--x25519", "--sealed", sealed.trim(), "--suri", "//Bob"]);
+	assert_eq!(opened.trim(), key);
+
+	// Another account derives another x25519 key and cannot open it.
+	let output = Command::new(cargo_bin("labor-node"))
+		.args(&["job-crypto", "open-key", "--x25519", "--sealed", sealed.trim()])
+		.args(&["--suri", "//Alice"])
+		.output()
+		.unwrap();
+	assert!(!output.status.success());
+}
@@ -0,0 +1,156 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use codec::Encode;
+use nix::sys::signal::{kill, Signal::SIGINT};
+use nix::unistd::Pid;
+use pallet_labor::{Completion, CompletionLeaf};
+use pallet_mmr_primitives::Proof;
+use serde_json::{json, Value};
+use sp_core::{crypto::Ss58Codec, hashing::twox_128, hexdisplay::HexDisplay, H256};
+use sp_keyring::AccountKeyring;
+use sp_runtime::traits::{BlakeTwo256, Hash as _};
+use std::{convert::TryInto, path::Path, process::Command, thread, time::Duration};
+use tempfile::tempdir;
+
+pub mod common;
+
+const RPC_PORT: u16 = 45944;
+
+fn tx(suri: &str, nonce: u32, call: Value) -> String {
+	let output = Command::new(cargo_bin("labor-node"))
+		.args(&["tx", "--dev", "--suri", suri, "--nonce", &nonce.to_string()])
+		.args(&["--call", &call.to_string()])
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	String::from_utf8(output.stdout).unwrap().trim().into()
+}
+
+/// Submits the extrinsic and waits until the node sealed block `number` with it.
+fn submit(extrinsic: &str, number: u32) {
+	let response = common::rpc(RPC_PORT, "author_submitExtrinsic", json!([extrinsic]));
+	assert!(response.contains("\"result\""), "submission failed: {}", response);
+	let number = format!("\"number\":\"{:#x}\"", number);
+	for _ in 0..30 {
+		if common::rpc(RPC_PORT, "chain_getHeader", json!([])).contains(&number) {
+			return
+		}
+		thread::sleep(Duration::from_secs(1));
+	}
+	panic!("the node did not seal the extrinsic");
+}
+
+fn verify_completion(proof: &Path, root: Option<&str>) -> (bool, String) {
+	let output = Command::new(cargo_bin("labor-node"))
+		.arg("verify-completion")
+		.arg(proof)
+		.args(root.map(|root| vec!["--root", root]).unwrap_or_default())
+		.output()
+		.unwrap();
+	(output.status.success(), String::from_utf8(output.stdout).unwrap())
+}
+
+fn result(response: &str) -> Value {
+	let body = &response[response.find("\r\n\r\n").map_or(0, |i| i + 4)..];
+	let mut body: Value = serde_json::from_str(body).unwrap();
+	body["result"].take()
+}
+
+#[test]
+fn completion_proofs_are_verified_offline() {
+	let base_path = tempdir().expect("could not create a temp dir");
+	let bob = AccountKeyring::Bob.to_account_id().to_ss58check();
+	let job = |call: &str, args: Value| {
+		json!({ "pallet": "LaborModule", "call": call, "args": args })
+	};
+	let post = json!({"details": "0x00", "payment": "1000", "worker_bond": "0"});
+	let extrinsics = [
+		tx("//Alice", 0, job("post_job", post)),
+		tx("//Bob", 0, job("apply", json!({"job_id": 0, "referrer": null}))),
+		tx("//Alice", 1, job("assign", json!({"job_id": 0, "worker": bob}))),
+		tx("//Alice", 2, job("approve", json!({"job_id": 0}))),
+		// The MMR leaf of the block with the approval is added by the next block.
+		tx("//Charlie", 0, json!({
+			"pallet": "Balances",
+			"call": "transfer",
+			"args": {"dest": bob, "value": "1000"},
+		})),
+	];
+
+	let mut node = Command::new(cargo_bin("labor-node"))
+		.args(&["--dev", "--sealing", "instant", "--rpc-port", &RPC_PORT.to_string()])
+		.args(&["--enable-offchain-indexing", "true", "-d"])
+		.arg(base_path.path())
+		.spawn()
+		.unwrap();
+	for (number, extrinsic) in (1..).zip(&extrinsics) {
+		submit(extrinsic, number);
+	}
+	let response = common::rpc(RPC_PORT, "labor_proveCompletion", json!([0]));
+	// The trusted root, read from the node rather than from the proof.
+	let root_key = [twox_128(b"Mmr"), twox_128(b"RootHash")].concat();
+	let root_key = format!("0x{}", HexDisplay::from(&root_key));
+	let root = result(&common::rpc(RPC_PORT, "state_getStorage", json!([root_key])));
+	kill(Pid::from_raw(node.id().try_into().unwrap()), SIGINT).unwrap();
+	assert!(common::wait_for(&mut node, 40).map(|x| x.success()).unwrap_or_default());
+
+	let mut proof = result(&response);
+	let root = root.as_str().expect("the MMR has a root");
+	assert_eq!(proof["root"], root);
+	assert_eq!(proof["worker"], bob, "unexpected proof: {}", response);
+	assert_eq!(proof["payment"], "1000");
+	assert_eq!(proof["rootBlock"], 5);
+
+	// The local database has the same leaf, so the command proves the same.
+	let output = Command::new(cargo_bin("labor-node"))
+		.args(&["labor", "prove", "0", "--dev", "-d"])
+		.arg(base_path.path())
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	assert_eq!(serde_json::from_slice::<Value>(&output.stdout).unwrap(), proof);
+
+	let proof_path = base_path.path().join("proof.json");
+	std::fs::write(&proof_path, proof.to_string()).unwrap();
+	let (verified, stdout) = verify_completion(&proof_path, Some(root));
+	assert!(verified, "the proof was rejected");
+	assert!(stdout.contains(&format!("{} completed job 0", bob)), "unexpected output: {}", stdout);
+
+	proof["payment"] = json!("1000000");
+	std::fs::write(&proof_path, proof.to_string()).unwrap();
+	assert!(!verify_completion(&proof_path, Some(root)).0, "a forged payment was accepted");
+}
+
+#[test]
+fn self_rooted_forged_proofs_are_rejected() {
+	let dir = tempdir().expect("could not create a temp dir");
+	let eve = AccountKeyring::Eve.to_account_id();
+	let block_hash = H256::repeat_byte(1);
+	let completion = Completion { job: 0, worker: eve.clone(), payment: 1_000_000u128 };
+	let leaf = CompletionLeaf { parent_hash: block_hash, completions: vec![completion] }.encode();
+	// A made up leaf is an MMR of its own, whose root is the hash of the leaf.
+	let forged_root = BlakeTwo256::hash(&leaf);
+	let proof = Proof::<H256> { leaf_index: 0, leaf_count: 1, items: vec![] };
+	let forged = json!({
+		"job": 0,
+		"worker": eve.to_ss58check(),
+		"payment": "1000000",
+		"blockHash": block_hash,
+		"leaf": format!("0x{}", HexDisplay::from(&leaf)),
+		"proof": format!("0x{}", HexDisplay::from(&proof.encode())),
+		"root": forged_root,
+		"rootBlock": 2,
+		"rootBlockHash": H256::repeat_byte(2),
+	});
+	let proof_path = dir.path().join("forged.json");
+	std::fs::write(&proof_path, forged.to_string()).unwrap();
+
+	// The proof is consistent, it only fails against a root that is trusted.
+	let forged_root = format!("{:?}", forged_root);
+	assert!(verify_completion(&proof_path, Some(&forged_root)).0, "the forged proof is invalid");
+	assert!(!verify_completion(&proof_path, None).0, "a proof was verified without a root");
+	let trusted_root = format!("{:?}", H256::repeat_byte(3));
+	let (verified, _) = verify_completion(&proof_path, Some(&trusted_root));
+	assert!(!verified, "the forged proof was accepted");
+}
@@ -0,0 +1,44 @@
+#![cfg(unix)]
+
+use serde_json::json;
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+use std::{thread, time::Duration};
+use tempfile::tempdir;
+
+pub mod common;
+
+const RPC_PORT: u16 = 45935;
+
+#[test]
+fn finalized_labor_events_are_indexed() {
+	let base_path = tempdir().expect("could not create a temp dir");
+
+	let extrinsic = common::post_job(base_path.path());
+	let index = base_path.path().join("labor-index");
+	let node = common::start_node(
+		base_path.path(),
+		&["--rpc-port", &RPC_PORT.to_string(), "--labor-index", index.to_str().unwrap()],
+	);
+
+	let submitted = common::rpc(RPC_PORT, "author_submitExtrinsic", json!([extrinsic]));
+	let alice = AccountKeyring::Alice.to_account_id().to_ss58check();
+	let query = json!([{ "account": alice, "job": 0 }]);
+	// Sealed blocks are finalized at once, the index follows shortly after.
+	let mut indexed = String::new();
+	for _ in 0..30 {
+		indexed = common::rpc(RPC_PORT, "labor_indexedEvents", query.clone());
+		if indexed.contains("JobPosted") {
+			break
+		}
+		thread::sleep(Duration::from_secs(1));
+	}
+	let bob = AccountKeyring::Bob.to_account_id().to_ss58check();
+	let other = common::rpc(RPC_PORT, "labor_indexedEvents", json!([{ "account": bob }]));
+
+	common::stop_node(node);
+	assert!(submitted.contains("\"result\""), "submission failed: {}", submitted);
+	assert!(indexed.contains("JobPosted"), "the job was not indexed: {}", indexed);
+	assert!(indexed.contains("\"payment\":\"1000\""), "unexpected event: {}", indexed);
+	assert!(other.contains("\"result\":[]"), "Bob has no events: {}", other);
+}
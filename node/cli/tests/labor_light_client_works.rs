@@ -0,0 +1,70 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use serde_json::{json, Value};
+use std::{process::Command, thread, time::Duration};
+use tempfile::tempdir;
+
+pub mod common;
+
+const FULL_RPC_PORT: u16 = 45938;
+const LIGHT_RPC_PORT: u16 = 45939;
+
+/// The peer id of the full node, derived from `--node-key` 1.
+const FULL_PEER_ID: &str = "12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp";
+
+/// The result of an RPC call, waiting up to a minute for one that is no `null`.
+fn result(port: u16, method: &str, params: Value) -> Value {
+	for _ in 0..60 {
+		let response = common::rpc(port, method, params.clone());
+		let body = &response[response.find("\r\n\r\n").map_or(0, |i| i + 4)..];
+		let mut body: Value = serde_json::from_str(body).unwrap_or_default();
+		if !body["result"].is_null() {
+			return body["result"].take()
+		}
+		thread::sleep(Duration::from_secs(1));
+	}
+	Value::Null
+}
+
+#[test]
+fn light_clients_read_jobs_from_full_nodes() {
+	let full_path = tempdir().expect("could not create a temp dir");
+	let light_path = tempdir().expect("could not create a temp dir");
+
+	let extrinsic = common::post_job(full_path.path());
+	// The light client verifies the BABE seals of the blocks, so the full node does not use
+	// `--sealing instant`.
+	let full = Command::new(cargo_bin("labor-node"))
+		.args(&["--dev", "--port", "45940", "--ws-port", "45941", "--no-prometheus"])
+		.args(&["--rpc-port", &FULL_RPC_PORT.to_string(), "--node-key", &format!("{:064x}", 1)])
+		.arg("-d")
+		.arg(full_path.path())
+		.spawn()
+		.unwrap();
+	let light = Command::new(cargo_bin("labor-node"))
+		.args(&["--chain", "dev", "--light", "--port", "45942", "--ws-port", "45943"])
+		.args(&["--rpc-port", &LIGHT_RPC_PORT.to_string(), "--no-prometheus", "--bootnodes"])
+		.arg(format!("/ip4/127.0.0.1/tcp/45940/p2p/{}", FULL_PEER_ID))
+		.arg("-d")
+		.arg(light_path.path())
+		.spawn()
+		.unwrap();
+
+	let submitted = common::rpc(FULL_RPC_PORT, "author_submitExtrinsic", json!([extrinsic]));
+	let job = result(FULL_RPC_PORT, "labor_getJob", json!([0]));
+	// The light client answers once it imported the block with the job.
+	let light_job = result(LIGHT_RPC_PORT, "labor_getJob", json!([0]));
+	let light_jobs = result(LIGHT_RPC_PORT, "labor_getJobs", json!([]));
+	let light_page = result(LIGHT_RPC_PORT, "labor_getJobs", json!([1, 10]));
+
+	common::stop_node(light);
+	common::stop_node(full);
+	assert!(submitted.contains("\"result\""), "submission failed: {}", submitted);
+	assert_eq!(job["id"], 0);
+	assert_eq!(job["payment"], "1000");
+	assert_eq!(light_job, job);
+	assert_eq!(light_jobs[0]["id"], 0);
+	assert_eq!(light_jobs[0]["details"], "Paint the fence");
+	assert_eq!(light_page, json!([]));
+}
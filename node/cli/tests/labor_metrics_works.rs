@@ -0,0 +1,126 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use serde_json::{json, Value};
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+use std::{
+	io::{Read, Write},
+	net::TcpStream,
+	process::Command,
+	thread,
+	time::Duration,
+};
+use tempfile::tempdir;
+
+pub mod common;
+
+const PROMETHEUS_PORT: u16 = 45934;
+const RPC_PORT: u16 = 45952;
+
+/// Scrapes the metrics, retrying until all `expected` samples are reported. Returns the last
+/// scrape and whether they were.
+fn scrape(expected: &[&str]) -> (String, bool) {
+	let mut response = String::new();
+	for _ in 0..30 {
+		if let Ok(mut stream) = TcpStream::connect(("127.0.0.1", PROMETHEUS_PORT)) {
+			write!(stream, "GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+				.unwrap();
+			response.clear();
+			stream.read_to_string(&mut response).unwrap();
+			if expected.iter().all(|sample| response.contains(&format!("\n{}\n", sample))) {
+				return (response, true)
+			}
+		}
+		thread::sleep(Duration::from_secs(1));
+	}
+	(response, false)
+}
+
+fn tx(suri: &str, nonce: u32, call: &str, args: Value) -> String {
+	let call = json!({ "pallet": "LaborModule", "call": call, "args": args });
+	let output = Command::new(cargo_bin("labor-node"))
+		.args(&["tx", "--dev", "--suri", suri, "--nonce", &nonce.to_string()])
+		.args(&["--call", &call.to_string()])
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	String::from_utf8(output.stdout).unwrap().trim().into()
+}
+
+/// Submits the extrinsic and waits until the node sealed block `number` with it.
+fn submit(extrinsic: &str, number: u32) -> bool {
+	let response = common::rpc(RPC_PORT, "author_submitExtrinsic", json!([extrinsic]));
+	if !response.contains("\"result\"") {
+		return false
+	}
+	let number = format!("\"number\":\"{:#x}\"", number);
+	for _ in 0..30 {
+		if common::rpc(RPC_PORT, "chain_getHeader", json!([])).contains(&number) {
+			return true
+		}
+		thread::sleep(Duration::from_secs(1));
+	}
+	false
+}
+
+#[test]
+fn labor_metrics_are_exported() {
+	let base_path = tempdir().expect("could not create a temp dir");
+	let bob = AccountKeyring::Bob.to_account_id().to_ss58check();
+
+	let node = common::start_node(
+		base_path.path(),
+		&["--rpc-port", &RPC_PORT.to_string(), "--prometheus-port", &PROMETHEUS_PORT.to_string()],
+	);
+
+	// The payment is escrowed once the job is posted, and the bond of the worker once assigned.
+	let steps = vec![
+		(
+			vec![
+				tx("//Alice", 0, "post_job", json!({
+					"details": "Paint the fence",
+					"payment": "1000",
+					"worker_bond": "100",
+				})),
+			],
+			vec!["labor_open_jobs 1", "labor_escrowed_value 1000", "labor_block_payouts 0"],
+		),
+		(
+			vec![
+				tx("//Bob", 0, "apply", json!({"job_id": 0, "referrer": null})),
+				tx("//Alice", 1, "assign", json!({"job_id": 0, "worker": bob})),
+			],
+			vec!["labor_open_jobs 0", "labor_escrowed_value 1100", "labor_block_payouts 0"],
+		),
+		(
+			vec![tx("//Alice", 2, "approve", json!({"job_id": 0}))],
+			vec!["labor_open_jobs 0", "labor_escrowed_value 0", "labor_block_payouts 1"],
+		),
+	];
+
+	let initial = scrape(&[
+		"labor_open_jobs 0",
+		"labor_escrowed_value 0",
+		"labor_disputes_in_progress 0",
+		"labor_block_payouts 0",
+	]);
+	let mut scrapes = vec![initial];
+	let mut number = 1;
+	for (extrinsics, expected) in &steps {
+		for extrinsic in extrinsics {
+			if !submit(extrinsic, number) {
+				break
+			}
+			number += 1;
+		}
+		scrapes.push(scrape(expected));
+	}
+
+	common::stop_node(node);
+
+	assert_eq!(number, 5, "the node did not seal all extrinsics");
+	for (step, (metrics, reported)) in scrapes.iter().enumerate() {
+		assert!(reported, "unexpected metrics after step {}:\n{}", step, metrics);
+	}
+}
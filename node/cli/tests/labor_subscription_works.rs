@@ -0,0 +1,92 @@
+#![cfg(unix)]
+
+use async_std::{future::timeout, net::TcpStream, task};
+use serde_json::{json, Value};
+use soketto::handshake::{Client, ServerResponse};
+use std::time::Duration;
+use tempfile::tempdir;
+
+pub mod common;
+
+const WS_PORT: u16 = 45937;
+
+/// Connects to the websocket RPC server, retrying while the node starts.
+async fn connect() -> (soketto::Sender<TcpStream>, soketto::Receiver<TcpStream>) {
+	for _ in 0..30 {
+		if let Ok(socket) = TcpStream::connect(("127.0.0.1", WS_PORT)).await {
+			let host = format!("127.0.0.1:{}", WS_PORT);
+			let mut client = Client::new(socket, &host, "/");
+			match client.handshake().await.unwrap() {
+				ServerResponse::Accepted { .. } => return client.into_builder().finish(),
+				_ => panic!("the websocket handshake was rejected"),
+			}
+		}
+		task::sleep(Duration::from_secs(1)).await;
+	}
+	panic!("the websocket server did not start");
+}
+
+async fn receive(receiver: &mut soketto::Receiver<TcpStream>) -> Value {
+	let mut message = Vec::new();
+	timeout(Duration::from_secs(30), receiver.receive_data(&mut message))
+		.await
+		.expect("no message from the node")
+		.unwrap();
+	serde_json::from_slice(&message).unwrap()
+}
+
+/// Receives notifications until one with `status` names a job.
+async fn job_update(receiver: &mut soketto::Receiver<TcpStream>, status: &str) -> Value {
+	loop {
+		let message = receive(receiver).await;
+		let update = &message["params"]["result"];
+		if update["status"] == status && !update["job"].is_null() {
+			return update.clone()
+		}
+	}
+}
+
+#[async_std::test]
+async fn job_updates_are_sent_for_best_and_finalized_blocks() {
+	let base_path = tempdir().expect("could not create a temp dir");
+
+	let extrinsic = common::post_job(base_path.path());
+	let node = common::start_node(base_path.path(), &["--ws-port", &WS_PORT.to_string()]);
+
+	let (mut sender, mut receiver) = connect().await;
+	let subscribe = json!({
+		"jsonrpc": "2.0",
+		"id": 1,
+		"method": "labor_subscribeJob",
+		"params": [0],
+	});
+	sender.send_text(subscribe.to_string()).await.unwrap();
+	sender.flush().await.unwrap();
+	let subscribed = receive(&mut receiver).await;
+	// The current state comes first, there is no job yet.
+	let finalized = receive(&mut receiver).await;
+	let best = receive(&mut receiver).await;
+
+	let submit = json!({
+		"jsonrpc": "2.0",
+		"id": 2,
+		"method": "author_submitExtrinsic",
+		"params": [extrinsic],
+	});
+	sender.send_text(submit.to_string()).await.unwrap();
+	sender.flush().await.unwrap();
+	let posted_best = job_update(&mut receiver, "best").await;
+	let posted_finalized = job_update(&mut receiver, "finalized").await;
+
+	common::stop_node(node);
+
+	assert!(subscribed["result"].is_string(), "subscription failed: {}", subscribed);
+	assert_eq!(finalized["params"]["result"]["status"], "finalized");
+	assert_eq!(best["params"]["result"]["status"], "best");
+	assert!(finalized["params"]["result"]["job"].is_null());
+	assert!(best["params"]["result"]["job"].is_null());
+	assert_eq!(posted_best["job"]["payment"], "1000");
+	assert_eq!(posted_best["job"]["status"], "Open");
+	assert_eq!(posted_finalized["block"], 1);
+	assert_eq!(posted_finalized["job"], posted_best["job"]);
+}
@@ -0,0 +1,205 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use serde_json::json;
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+use std::{
+	io::{BufRead, BufReader, Read, Write},
+	net::{TcpListener, TcpStream},
+	path::Path,
+	process::{Child, Command},
+	sync::mpsc,
+	thread,
+	time::Duration,
+};
+use tempfile::tempdir;
+
+pub mod common;
+
+const RPC_PORT: u16 = 45936;
+#[cfg(feature = "sudo")]
+const RESOLVE_RPC_PORT: u16 = 45953;
+
+/// Reads an HTTP request and returns its body.
+fn read_request(stream: &mut TcpStream) -> String {
+	let mut reader = BufReader::new(stream);
+	let mut content_length = 0;
+	loop {
+		let mut line = String::new();
+		reader.read_line(&mut line).unwrap();
+		if line == "\r\n" {
+			break
+		}
+		if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+			content_length = length.trim().parse().unwrap();
+		}
+	}
+	let mut body = vec![0; content_length];
+	reader.read_exact(&mut body).unwrap();
+	String::from_utf8(body).unwrap()
+}
+
+/// An HTTP endpoint that fails the first request and accepts all others, sending the bodies of
+/// all requests.
+fn stand_in() -> (u16, mpsc::Receiver<String>) {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let port = listener.local_addr().unwrap().port();
+	let (tx, rx) = mpsc::channel();
+	thread::spawn(move || {
+		for (attempt, stream) in listener.incoming().enumerate() {
+			let mut stream = stream.unwrap();
+			let body = read_request(&mut stream);
+			let status = if attempt == 0 { "500 Internal Server Error" } else { "200 OK" };
+			write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status)
+				.unwrap();
+			let _ = tx.send(body);
+		}
+	});
+	(port, rx)
+}
+
+fn tx(suri: &str, nonce: u32, call: serde_json::Value) -> String {
+	let output = Command::new(cargo_bin("labor-node"))
+		.args(&["tx", "--dev", "--suri", suri, "--nonce", &nonce.to_string()])
+		.args(&["--call", &call.to_string()])
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	String::from_utf8(output.stdout).unwrap().trim().into()
+}
+
+/// Writes a webhooks config with a single endpoint at `port` subscribed to `events`.
+fn write_config(path: &Path, port: u16, events: &str) {
+	std::fs::write(
+		path,
+		format!(
+			"[[endpoint]]\nname = \"test\"\nurl = \"http://127.0.0.1:{}/hook\"\n\
+			events = [\"{}\"]\n",
+			port, events,
+		),
+	)
+	.unwrap();
+}
+
+fn start_node(rpc_port: u16, base_path: &Path, webhooks: &Path) -> Child {
+	common::start_node(
+		base_path,
+		&["--rpc-port", &rpc_port.to_string(), "--labor-webhooks", webhooks.to_str().unwrap()],
+	)
+}
+
+/// Submits the extrinsic and waits until the node sealed block `number` with it.
+fn submit(rpc_port: u16, extrinsic: &str, number: u32) {
+	let response = common::rpc(rpc_port, "author_submitExtrinsic", json!([extrinsic]));
+	assert!(response.contains("\"result\""), "submission failed: {}", response);
+	let number = format!("\"number\":\"{:#x}\"", number);
+	for _ in 0..30 {
+		if common::rpc(rpc_port, "chain_getHeader", json!([])).contains(&number) {
+			return
+		}
+		thread::sleep(Duration::from_secs(1));
+	}
+	panic!("the node did not seal the extrinsic");
+}
+
+#[test]
+fn assigned_jobs_are_delivered_once_across_restarts() {
+	let base_path = tempdir().expect("could not create a temp dir");
+	let (port, requests) = stand_in();
+	let webhooks = base_path.path().join("labor-webhooks.toml");
+	write_config(&webhooks, port, "jobAssigned");
+
+	let bob = AccountKeyring::Bob.to_account_id().to_ss58check();
+	let post = tx("//Alice", 0, json!({
+		"pallet": "LaborModule",
+		"call": "post_job",
+		"args": {"details": "0x00", "payment": "1000", "worker_bond": "0"},
+	}));
+	let apply = tx("//Bob", 0, json!({
+		"pallet": "LaborModule",
+		"call": "apply",
+		"args": {"job_id": 0, "referrer": null},
+	}));
+	let assign = tx("//Alice", 1, json!({
+		"pallet": "LaborModule",
+		"call": "assign",
+		"args": {"job_id": 0, "worker": bob},
+	}));
+
+	let node = start_node(RPC_PORT, base_path.path(), &webhooks);
+	submit(RPC_PORT, &post, 1);
+	submit(RPC_PORT, &apply, 2);
+	submit(RPC_PORT, &assign, 3);
+	// The first delivery fails, the retry a second later succeeds.
+	let failed = requests.recv_timeout(Duration::from_secs(30));
+	let delivered = requests.recv_timeout(Duration::from_secs(30));
+	common::stop_node(node);
+
+	let failed = failed.expect("the event should have been delivered");
+	assert_eq!(delivered.expect("the delivery should have been retried"), failed);
+	let payload: serde_json::Value = serde_json::from_str(&failed).unwrap();
+	assert_eq!(payload["event"], "jobAssigned");
+	assert_eq!(payload["job"], 0);
+	assert_eq!(payload["worker"], bob);
+	assert_eq!(payload["block"], 3);
+
+	// The cursor is past the delivered event, so a restart delivers nothing again.
+	let node = start_node(RPC_PORT, base_path.path(), &webhooks);
+	let redelivered = requests.recv_timeout(Duration::from_secs(10));
+	common::stop_node(node);
+	assert!(redelivered.is_err(), "delivered again: {:?}", redelivered);
+}
+
+#[cfg(feature = "sudo")]
+#[test]
+fn resolved_disputes_report_the_payment_less_the_commission() {
+	use codec::Encode;
+	use labor_node_runtime::Call;
+	use sp_core::hexdisplay::HexDisplay;
+
+	let base_path = tempdir().expect("could not create a temp dir");
+	let (port, requests) = stand_in();
+	let webhooks = base_path.path().join("labor-webhooks.toml");
+	write_config(&webhooks, port, "paymentReleased");
+
+	let bob = AccountKeyring::Bob.to_account_id().to_ss58check();
+	let charlie = AccountKeyring::Charlie.to_account_id().to_ss58check();
+	let labor = |suri, nonce, call, args| {
+		tx(suri, nonce, json!({"pallet": "LaborModule", "call": call, "args": args}))
+	};
+	let resolve = Call::LaborModule(pallet_labor::Call::resolve(0, true)).encode();
+	let extrinsics = vec![
+		labor("//Alice", 0, "post_job", json!({
+			"details": "0x00", "payment": "1000", "worker_bond": "0",
+		})),
+		labor("//Bob", 0, "apply", json!({"job_id": 0, "referrer": charlie})),
+		labor("//Alice", 1, "assign", json!({"job_id": 0, "worker": bob})),
+		labor("//Bob", 1, "dispute", json!({"job_id": 0})),
+		tx("//Alice", 2, json!({
+			"pallet": "Sudo",
+			"call": "sudo",
+			"args": {"call": {"scale": format!("0x{}", HexDisplay::from(&resolve))}},
+		})),
+	];
+
+	let node = start_node(RESOLVE_RPC_PORT, base_path.path(), &webhooks);
+	for (number, extrinsic) in (1..).zip(&extrinsics) {
+		submit(RESOLVE_RPC_PORT, extrinsic, number);
+	}
+	// The first delivery fails, the retry a second later succeeds.
+	let failed = requests.recv_timeout(Duration::from_secs(30));
+	let delivered = requests.recv_timeout(Duration::from_secs(30));
+	common::stop_node(node);
+
+	let failed = failed.expect("the event should have been delivered");
+	assert_eq!(delivered.expect("the delivery should have been retried"), failed);
+	let payload: serde_json::Value = serde_json::from_str(&failed).unwrap();
+	assert_eq!(payload["event"], "paymentReleased");
+	assert_eq!(payload["job"], 0);
+	assert_eq!(payload["worker"], bob);
+	// Charlie referred Bob and received 5% of the payment.
+	assert_eq!(payload["payment"], "950");
+	assert_eq!(payload["disputed"], true);
+	assert_eq!(payload["block"], 5);
+}
@@ -0,0 +1,100 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use nix::sys::signal::{kill, Signal::SIGINT};
+use nix::unistd::Pid;
+use serde_json::{json, Value};
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+use std::{convert::TryInto, path::Path, process::Command, thread, time::Duration};
+use tempfile::tempdir;
+
+pub mod common;
+
+const RPC_PORT: u16 = 45949;
+
+/// Runs `labor-node labor <args>` against the database at `base_path`.
+fn labor(base_path: &Path, args: &[&str]) -> String {
+	let output = Command::new(cargo_bin("labor-node"))
+		.arg("labor")
+		.args(args)
+		.args(&["--dev", "-d"])
+		.arg(base_path)
+		.output()
+		.unwrap();
+	assert!(output.status.success(), "labor {:?} failed", args);
+	String::from_utf8(output.stdout).unwrap()
+}
+
+fn tx(suri: &str, nonce: u32, call: Value) -> String {
+	let output = Command::new(cargo_bin("labor-node"))
+		.args(&["tx", "--dev", "--suri", suri, "--nonce", &nonce.to_string()])
+		.args(&["--call", &call.to_string()])
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	String::from_utf8(output.stdout).unwrap().trim().into()
+}
+
+/// Runs an instant sealing node on `base_path`, submitting the extrinsics one block at a time.
+fn seal(base_path: &Path, first_block: u32, extrinsics: &[String]) {
+	let mut node = Command::new(cargo_bin("labor-node"))
+		.args(&["--dev", "--sealing", "instant", "--rpc-port", &RPC_PORT.to_string(), "-d"])
+		.arg(base_path)
+		.spawn()
+		.unwrap();
+	for (number, extrinsic) in (first_block..).zip(extrinsics) {
+		let response = common::rpc(RPC_PORT, "author_submitExtrinsic", json!([extrinsic]));
+		assert!(response.contains("\"result\""), "submission failed: {}", response);
+		let number = format!("\"number\":\"{:#x}\"", number);
+		let mut sealed = false;
+		for _ in 0..30 {
+			sealed = common::rpc(RPC_PORT, "chain_getHeader", json!([])).contains(&number);
+			if sealed {
+				break
+			}
+			thread::sleep(Duration::from_secs(1));
+		}
+		assert!(sealed, "the node did not seal the extrinsic");
+	}
+	kill(Pid::from_raw(node.id().try_into().unwrap()), SIGINT).unwrap();
+	assert!(common::wait_for(&mut node, 40).map(|x| x.success()).unwrap_or_default());
+}
+
+#[test]
+fn jobs_are_posted_listed_and_approved_from_the_local_database() {
+	let base_path = tempdir().expect("could not create a temp dir");
+	let base_path = base_path.path();
+	let bob = AccountKeyring::Bob.to_account_id().to_ss58check();
+
+	let post = labor(
+		base_path,
+		&["post", "--suri", "//Alice", "--details", "Paint the fence", "--payment", "1000"],
+	);
+	let job = |call: &str, args: Value| {
+		json!({ "pallet": "LaborModule", "call": call, "args": args })
+	};
+	seal(base_path, 1, &[
+		post.trim().into(),
+		tx("//Bob", 0, job("apply", json!({"job_id": 0, "referrer": null}))),
+		tx("//Alice", 1, job("assign", json!({"job_id": 0, "worker": bob}))),
+	]);
+
+	let jobs: Value = serde_json::from_str(&labor(base_path, &["list"])).unwrap();
+	assert_eq!(jobs[0]["id"], 0);
+	assert_eq!(jobs[0]["details"], "Paint the fence");
+	assert_eq!(jobs[0]["payment"], "1000");
+	assert_eq!(jobs[0]["status"], "Assigned");
+	let open: Value = serde_json::from_str(&labor(base_path, &["list", "--open"])).unwrap();
+	assert_eq!(open, json!([]));
+
+	let shown: Value = serde_json::from_str(&labor(base_path, &["show", "0"])).unwrap();
+	assert_eq!(shown["worker"], bob);
+	assert_eq!(shown["applicants"], json!([{ "worker": bob, "referrer": null }]));
+
+	// The nonce of Alice is read from the local database.
+	let approve = labor(base_path, &["approve", "0", "--suri", "//Alice"]);
+	seal(base_path, 4, &[approve.trim().into()]);
+	let shown: Value = serde_json::from_str(&labor(base_path, &["show", "0"])).unwrap();
+	assert_eq!(shown["status"], "Completed");
+}
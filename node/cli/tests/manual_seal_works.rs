@@ -0,0 +1,58 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use nix::sys::signal::{kill, Signal::SIGINT};
+use nix::unistd::Pid;
+use std::{
+	convert::TryInto,
+	io::{BufRead, BufReader},
+	process::{Command, Stdio},
+	sync::mpsc,
+	thread,
+	time::Duration,
+};
+use tempfile::tempdir;
+
+pub mod common;
+
+#[test]
+fn interval_sealing_authors_blocks_without_waiting_for_slots() {
+	let base_path = tempdir().expect("could not create a temp dir");
+
+	let mut node = Command::new(cargo_bin("labor-node"))
+		.args(&["--dev", "--sealing", "interval=100", "-d"])
+		.arg(base_path.path())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+
+	let stderr = BufReader::new(node.stderr.take().unwrap());
+	let (tx, rx) = mpsc::channel();
+	// Keep draining STDERR after the match, so the node never writes to a closed pipe.
+	thread::spawn(move || {
+		for line in stderr.lines().filter_map(Result::ok) {
+			if line.contains("Imported #10") {
+				let _ = tx.send(true);
+			}
+		}
+	});
+
+	// Ten BABE slots would take 30 seconds.
+	let imported = rx.recv_timeout(Duration::from_secs(20)).unwrap_or(false);
+
+	kill(Pid::from_raw(node.id().try_into().unwrap()), SIGINT).unwrap();
+	assert!(common::wait_for(&mut node, 40).map(|x| x.success()).unwrap_or_default());
+	assert!(imported, "the node should have sealed ten blocks");
+}
+
+#[test]
+fn sealing_requires_a_development_chain() {
+	let base_path = tempdir().expect("could not create a temp dir");
+
+	let status = Command::new(cargo_bin("labor-node"))
+		.args(&["--chain", "local", "--sealing", "instant", "-d"])
+		.arg(base_path.path())
+		.status()
+		.unwrap();
+	assert!(!status.success());
+}
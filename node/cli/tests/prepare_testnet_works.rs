@@ -0,0 +1,82 @@
+use assert_cmd::cargo::cargo_bin;
+use std::{path::Path, process::Command};
+use tempfile::tempdir;
+
+/// The contents of all files below `dir`.
+fn contents(dir: &Path) -> Vec<Vec<u8>> {
+	std::fs::read_dir(dir)
+		.unwrap()
+		.flat_map(|entry| {
+			let path = entry.unwrap().path();
+			if path.is_dir() {
+				contents(&path)
+			} else {
+				vec![std::fs::read(path).unwrap()]
+			}
+		})
+		.collect()
+}
+
+#[test]
+fn prepare_testnet_writes_chain_spec_and_keystores() {
+	let dir = tempdir().expect("could not create a temp dir");
+	let secret_file = dir.path().join("secret");
+	std::fs::write(&secret_file, format!("{}\n", sp_core::crypto::DEV_PHRASE)).unwrap();
+	let output = dir.path().join("testnet");
+
+	let status = Command::new(cargo_bin("labor-node"))
+		.args(&["prepare-testnet", "--validators", "2", "--secret-file"])
+		.arg(&secret_file)
+		.arg("--output")
+		.arg(&output)
+		.status()
+		.unwrap();
+	assert!(status.success());
+
+	let spec: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(output.join("chain_spec.json")).unwrap()).unwrap();
+	assert_eq!(spec["id"], "labor_testnet");
+	assert!(spec["genesis"]["raw"]["top"].as_object().map_or(false, |top| !top.is_empty()));
+
+	for validator in &["validator-1", "validator-2"] {
+		let keystore = output.join(validator).join("chains/labor_testnet/keystore");
+		let mut key_types = std::fs::read_dir(&keystore)
+			.unwrap()
+			.map(|entry| entry.unwrap().file_name().to_string_lossy()[..8].to_string())
+			.collect::<Vec<_>>();
+		key_types.sort();
+		// `audi`, `babe`, `gran` and `imon` in hex.
+		assert_eq!(key_types, vec!["61756469", "62616265", "6772616e", "696d6f6e"]);
+	}
+	assert!(!output.join("validator-3").exists());
+}
+
+#[test]
+fn prepare_testnet_keeps_the_secret_out_of_the_keystores() {
+	let dir = tempdir().expect("could not create a temp dir");
+	let secret_file = dir.path().join("secret");
+	std::fs::write(&secret_file, format!("{}\n", sp_core::crypto::DEV_PHRASE)).unwrap();
+	let output = dir.path().join("testnet");
+
+	let status = Command::new(cargo_bin("labor-node"))
+		.args(&["prepare-testnet", "--validators", "2", "--secret-file"])
+		.arg(&secret_file)
+		.arg("--output")
+		.arg(&output)
+		.status()
+		.unwrap();
+	assert!(status.success());
+
+	let secret = std::fs::read_to_string(&secret_file).unwrap();
+	let secret = secret.trim();
+	for validator in &["validator-1", "validator-2"] {
+		let files = contents(&output.join(validator));
+		assert_eq!(files.len(), 4);
+		for file in files {
+			let file = String::from_utf8(file).unwrap();
+			assert!(!file.contains(secret));
+			// Each key file holds the hex seed of the derived key, as a JSON string.
+			assert!(file.starts_with("\"0x"), "unexpected key file {}", file);
+		}
+	}
+}
@@ -0,0 +1,80 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use nix::sys::signal::{kill, Signal::SIGINT};
+use nix::unistd::Pid;
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+use std::{
+	convert::TryInto,
+	io::{BufRead, BufReader},
+	path::Path,
+	process::{Command, Output, Stdio},
+	sync::mpsc,
+	thread,
+	time::Duration,
+};
+use tempfile::tempdir;
+
+pub mod common;
+
+const RPC_PORT: u16 = 45933;
+
+fn verify(base_path: &Path, keys: &str) -> Output {
+	let controller = AccountKeyring::Alice.to_account_id().to_ss58check();
+	Command::new(cargo_bin("labor-node"))
+		.args(&["session", "verify", "--dev", "--controller", &controller, "--keys", keys, "-d"])
+		.arg(base_path)
+		.output()
+		.unwrap()
+}
+
+#[test]
+fn rotated_session_keys_are_registered_once_included() {
+	let base_path = tempdir().expect("could not create a temp dir");
+
+	let output = Command::new(cargo_bin("labor-node"))
+		.args(&["session", "rotate", "--dev", "--suri", "//Alice", "-d"])
+		.arg(base_path.path())
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	let rotation: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	let keys = rotation["keys"].as_str().unwrap();
+	let extrinsic = rotation["extrinsic"].as_str().unwrap();
+	let keystore = base_path.path().join("chains/dev/keystore");
+	assert_eq!(std::fs::read_dir(&keystore).unwrap().count(), 4);
+
+	// Not registered before the extrinsic is in a block.
+	let output = verify(base_path.path(), keys);
+	assert!(!output.status.success());
+	assert!(String::from_utf8_lossy(&output.stderr).contains("are not the given keys"));
+
+	let mut node = Command::new(cargo_bin("labor-node"))
+		.args(&["--dev", "--sealing", "instant", "--rpc-port", &RPC_PORT.to_string(), "-d"])
+		.arg(base_path.path())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+	let stderr = BufReader::new(node.stderr.take().unwrap());
+	let (tx, rx) = mpsc::channel();
+	// Keep draining STDERR after the match, so the node never writes to a closed pipe.
+	thread::spawn(move || {
+		for line in stderr.lines().filter_map(Result::ok) {
+			if line.contains("Imported #1 ") {
+				let _ = tx.send(true);
+			}
+		}
+	});
+
+	let response = common::rpc(RPC_PORT, "author_submitExtrinsic", serde_json::json!([extrinsic]));
+	let imported = rx.recv_timeout(Duration::from_secs(30)).unwrap_or(false);
+	kill(Pid::from_raw(node.id().try_into().unwrap()), SIGINT).unwrap();
+	assert!(common::wait_for(&mut node, 40).map(|x| x.success()).unwrap_or_default());
+	assert!(response.contains("\"result\""), "submission failed: {}", response);
+	assert!(imported, "the node should have sealed the extrinsic");
+
+	let output = verify(base_path.path(), keys);
+	assert!(output.status.success());
+	assert!(String::from_utf8_lossy(&output.stdout).contains("are registered"));
+}
@@ -0,0 +1,95 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use serde_json::{json, Value};
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+use std::{path::Path, process::Command, thread, time::Duration};
+use tempfile::tempdir;
+
+pub mod common;
+
+const RPC_PORT: u16 = 45951;
+
+fn storage(base_path: &Path, args: &[&str]) -> Option<Value> {
+	let output = Command::new(cargo_bin("labor-node"))
+		.arg("storage")
+		.args(args)
+		.args(&["--dev", "-d"])
+		.arg(base_path)
+		.output()
+		.unwrap();
+	if !output.status.success() {
+		return None
+	}
+	Some(serde_json::from_slice(&output.stdout).unwrap())
+}
+
+/// Sign a call of the labor pallet as Alice, with `args` given as JSON.
+fn tx(nonce: u32, call: &str, args: Value) -> String {
+	let call = json!({"pallet": "LaborModule", "call": call, "args": args});
+	let output = Command::new(cargo_bin("labor-node"))
+		.args(&["tx", "--dev", "--suri", "//Alice", "--nonce", &nonce.to_string()])
+		.args(&["--call", &call.to_string()])
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	String::from_utf8(output.stdout).unwrap().trim().into()
+}
+
+#[test]
+fn storage_items_are_decoded_from_the_local_database() {
+	let base_path = tempdir().expect("could not create a temp dir");
+
+	// `StructValue { number: 7, string: b"seven" }`, which `tx` cannot encode from JSON.
+	let struct_value = json!({"scale": "0x0714736576656e"});
+	let extrinsics = vec![
+		common::post_job(base_path.path()),
+		tx(1, "do_some_struct", json!([struct_value])),
+		tx(2, "do_some_map", json!([7, struct_value])),
+	];
+
+	let node = common::start_node(base_path.path(), &["--rpc-port", &RPC_PORT.to_string()]);
+	let submitted = extrinsics
+		.iter()
+		.map(|extrinsic| common::rpc(RPC_PORT, "author_submitExtrinsic", json!([extrinsic])))
+		.collect::<Vec<_>>();
+	let mut sealed = false;
+	for _ in 0..30 {
+		sealed = common::rpc(RPC_PORT, "chain_getHeader", json!([])).contains("\"number\":\"0x3\"");
+		if sealed {
+			break
+		}
+		thread::sleep(Duration::from_secs(1));
+	}
+	common::stop_node(node);
+	for submitted in submitted {
+		assert!(submitted.contains("\"result\""), "submission failed: {}", submitted);
+	}
+	assert!(sealed, "the node did not seal the extrinsics");
+
+	let base_path = base_path.path();
+	assert_eq!(storage(base_path, &["LaborModule::NextJobId"]), Some(json!(1)));
+	assert_eq!(storage(base_path, &["LaborModule::NextJobId", "--at", "0"]), Some(json!(0)));
+
+	let jobs = storage(base_path, &["LaborModule::Jobs"]).unwrap();
+	let alice = AccountKeyring::Alice.to_account_id().to_ss58check();
+	assert_eq!(jobs[0]["key"], json!([0]));
+	assert_eq!(jobs[0]["value"]["employer"], alice);
+	assert_eq!(jobs[0]["value"]["details"], "Paint the fence");
+	assert_eq!(jobs[0]["value"]["payment"], "1000");
+	assert_eq!(storage(base_path, &["LaborModule::Jobs", "--at", "0"]), Some(json!([])));
+
+	let struct_value = json!({"number": 7, "string": "seven"});
+	assert_eq!(storage(base_path, &["LaborModule::SomeStruct"]), Some(struct_value.clone()));
+	let map = storage(base_path, &["LaborModule::SomeMap"]).unwrap();
+	assert_eq!(map.as_array().map(Vec::len), Some(1));
+	assert_eq!(map[0]["value"], struct_value);
+	assert_eq!(storage(base_path, &["LaborModule::SomeStruct", "--at", "0"]), Some(Value::Null));
+
+	// Items of composite types from other pallets are rejected rather than printed as hex.
+	assert!(storage(base_path, &["System::Account"]).is_none());
+
+	assert!(storage(base_path, &["LaborModule::Unknown"]).is_none());
+	assert!(storage(base_path, &["Unknown::Jobs"]).is_none());
+}
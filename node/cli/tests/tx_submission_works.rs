@@ -0,0 +1,75 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use nix::sys::signal::{kill, Signal::SIGINT};
+use nix::unistd::Pid;
+use serde_json::{json, Value};
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+use std::{convert::TryInto, process::Command, thread, time::Duration};
+use tempfile::tempdir;
+
+pub mod common;
+
+const RPC_PORT: u16 = 45950;
+
+fn tx(nonce: u32, era: &[&str]) -> String {
+	let bob = AccountKeyring::Bob.to_account_id().to_ss58check();
+	let call = json!({
+		"pallet": "Balances",
+		"call": "transfer",
+		"args": {"dest": bob, "value": "1000"},
+	});
+	let output = Command::new(cargo_bin("labor-node"))
+		.args(&["tx", "--dev", "--suri", "//Alice", "--nonce", &nonce.to_string()])
+		.args(&["--call", &call.to_string()])
+		.args(era)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	String::from_utf8(output.stdout).unwrap().trim().into()
+}
+
+fn result(method: &str, params: Value) -> Value {
+	let response = common::rpc(RPC_PORT, method, params);
+	let body = &response[response.find("\r\n\r\n").map_or(0, |i| i + 4)..];
+	let mut body: Value = serde_json::from_str(body).unwrap_or_default();
+	body["result"].take()
+}
+
+/// Submits the extrinsic and waits until the node sealed block `number` with it.
+fn submit(extrinsic: &str, number: u32) {
+	let response = common::rpc(RPC_PORT, "author_submitExtrinsic", json!([extrinsic]));
+	assert!(response.contains("\"result\""), "the node rejected the extrinsic: {}", response);
+	for _ in 0..30 {
+		if result("chain_getHeader", json!([]))["number"] == format!("{:#x}", number) {
+			return
+		}
+		thread::sleep(Duration::from_secs(1));
+	}
+	panic!("the node did not seal the extrinsic");
+}
+
+#[test]
+fn offline_extrinsics_are_accepted_by_a_dev_node() {
+	let base_path = tempdir().expect("could not create a temp dir");
+	let mut node = Command::new(cargo_bin("labor-node"))
+		.args(&["--dev", "--sealing", "instant", "--rpc-port", &RPC_PORT.to_string(), "-d"])
+		.arg(base_path.path())
+		.spawn()
+		.unwrap();
+
+	// The genesis hash and runtime version derived from the chain spec match the node's.
+	submit(&tx(0, &[]), 1);
+	let block_hash = result("chain_getBlockHash", json!([1]));
+	let block_hash = block_hash.as_str().expect("block 1 exists");
+	let era = ["--mortality", "64", "--era-block-number", "1", "--era-block-hash", block_hash];
+	let mortal = tx(1, &era);
+	submit(&mortal, 2);
+	let alice = AccountKeyring::Alice.to_account_id().to_ss58check();
+	let nonce = result("system_accountNextIndex", json!([alice]));
+
+	kill(Pid::from_raw(node.id().try_into().unwrap()), SIGINT).unwrap();
+	assert!(common::wait_for(&mut node, 40).map(|x| x.success()).unwrap_or_default());
+	assert_eq!(nonce, 2);
+}
@@ -0,0 +1,26 @@
+use assert_cmd::cargo::cargo_bin;
+use codec::Decode;
+use labor_node_runtime::{BalancesCall, Call, UncheckedExtrinsic};
+use sp_core::bytes::from_hex;
+use sp_keyring::AccountKeyring;
+use std::process::Command;
+
+#[test]
+fn tx_builds_signed_extrinsic_from_json() {
+	let bob = AccountKeyring::Bob.to_account_id();
+	let call = format!(
+		r#"{{"pallet": "Balances", "call": "transfer", "args": {{"dest": "{}", "value": "1000"}}}}"#,
+		sp_core::crypto::Ss58Codec::to_ss58check(&bob),
+	);
+
+	let output = Command::new(cargo_bin("labor-node"))
+		.args(&["tx", "--dev", "--suri", "//Alice", "--nonce", "3", "--call", &call])
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+
+	let hex = String::from_utf8(output.stdout).unwrap();
+	let extrinsic = UncheckedExtrinsic::decode(&mut &from_hex(hex.trim()).unwrap()[..]).unwrap();
+	assert!(extrinsic.signature.is_some());
+	assert_eq!(extrinsic.function, Call::Balances(BalancesCall::transfer(bob.into(), 1000)));
+}
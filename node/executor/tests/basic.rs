@@ -34,6 +34,7 @@ use labor_node_runtime::{
 	constants::{time::SLOT_DURATION, currency::*},
 };
 use node_primitives::{Balance, Hash};
+#[cfg(feature = "contracts")]
 use wat;
 use node_testing::keyring::*;
 
@@ -500,6 +501,7 @@ fn full_wasm_block_import_works() {
 	});
 }
 
+#[cfg(feature = "contracts")]
 const CODE_TRANSFER: &str = r#"
 (module
 ;; seal_call(
@@ -596,6 +598,7 @@ const CODE_TRANSFER: &str = r#"
 "#;
 
 #[test]
+#[cfg(feature = "contracts")]
 fn deploying_wasm_contract_should_work() {
 	let transfer_code = wat::parse_str(CODE_TRANSFER).unwrap();
 	let transfer_ch = <Runtime as frame_system::Config>::Hashing::hash(&transfer_code);
@@ -0,0 +1,152 @@
+//! Root-only calls keep working without sudo, through referenda.
+
+use codec::Encode;
+use frame_support::{
+	assert_ok,
+	storage::migration::{have_storage_value, put_storage_value},
+	traits::{ChangeMembers, Get, OnInitialize, OnRuntimeUpgrade},
+	weights::GetDispatchInfo,
+};
+use sp_core::NeverNativeValue;
+use sp_runtime::traits::{BlakeTwo256, Hash as HashT};
+use pallet_democracy::{AccountVote, Conviction, Vote, VoteThreshold};
+use labor_node_runtime::{
+	CheckedExtrinsic, Call, Council, Democracy, LaborModule, Origin, Runtime, Scheduler, System,
+	constants::{time::SLOT_DURATION, currency::*},
+	migrations::RemoveSudoKey,
+};
+use node_testing::keyring::*;
+
+pub mod common;
+use self::common::*;
+
+/// An admin action of the labor pallet, which the runtime only lets root take.
+fn ban_charlie() -> Call {
+	Call::LaborModule(pallet_labor::Call::ban(charlie()))
+}
+
+/// The test genesis with Alice and Bob on the Council.
+fn new_test_ext_with_council() -> TestExternalities<BlakeTwo256> {
+	let mut t = new_test_ext(compact_code_unwrap(), false);
+	let mut members = vec![alice(), bob()];
+	members.sort();
+	t.execute_with(|| Council::set_members_sorted(&members, &[]));
+	t
+}
+
+#[test]
+fn council_external_proposal_takes_labor_admin_actions_through_a_referendum() {
+	let proposal = ban_charlie().encode();
+	let proposal_hash = BlakeTwo256::hash(&proposal);
+	let motion = Call::Democracy(pallet_democracy::Call::external_propose_majority(proposal_hash));
+	let motion_hash = <Runtime as frame_system::Config>::Hashing::hash_of(&motion);
+	let motion_len = motion.encoded_size() as u32;
+	let motion_weight = motion.get_dispatch_info().weight;
+
+	let time = 42 * 1000;
+	let block = construct_block(
+		&mut new_test_ext_with_council(),
+		1,
+		GENESIS_HASH.into(),
+		vec![
+			CheckedExtrinsic {
+				signed: None,
+				function: Call::Timestamp(pallet_timestamp::Call::set(time)),
+			},
+			CheckedExtrinsic {
+				signed: Some((alice(), signed_extra(0, 0))),
+				function: Call::Council(
+					pallet_collective::Call::propose(2, Box::new(motion), motion_len)
+				),
+			},
+			CheckedExtrinsic {
+				signed: Some((bob(), signed_extra(0, 0))),
+				function: Call::Council(pallet_collective::Call::vote(motion_hash, 0, true)),
+			},
+			CheckedExtrinsic {
+				signed: Some((alice(), signed_extra(1, 0))),
+				function: Call::Council(
+					pallet_collective::Call::close(motion_hash, 0, motion_weight, motion_len)
+				),
+			},
+		],
+		(time / SLOT_DURATION).into(),
+	);
+
+	let mut t = new_test_ext_with_council();
+	executor_call::<NeverNativeValue, fn() -> _>(
+		&mut t,
+		"Core_execute_block",
+		&block.0,
+		true,
+		None,
+	).0.unwrap();
+
+	t.execute_with(|| {
+		// The Council cannot take the action itself, it only tables the referendum.
+		assert!(LaborModule::banned(charlie()).is_none());
+		assert_ok!(Democracy::note_preimage(Origin::signed(alice()), proposal));
+
+		let launch = <Runtime as pallet_democracy::Config>::LaunchPeriod::get();
+		System::set_block_number(launch);
+		Democracy::on_initialize(launch);
+		let vote = Vote { aye: true, conviction: Conviction::None };
+		assert_ok!(Democracy::vote(
+			Origin::signed(alice()),
+			0,
+			AccountVote::Standard { vote, balance: 10 * DOLLARS },
+		));
+
+		// The referendum passes at its end and is enacted after the enactment period.
+		let end = Democracy::referendum_status(0).unwrap().end;
+		System::set_block_number(end);
+		Democracy::on_initialize(end);
+		let enactment = end + <Runtime as pallet_democracy::Config>::EnactmentPeriod::get();
+		System::set_block_number(enactment);
+		Scheduler::on_initialize(enactment);
+
+		assert!(LaborModule::banned(charlie()).is_some());
+	});
+}
+
+#[test]
+fn referendum_takes_labor_admin_actions() {
+	let mut t = new_test_ext(compact_code_unwrap(), false);
+	t.execute_with(|| {
+		System::set_block_number(1);
+		assert!(LaborModule::ban(Origin::signed(alice()), charlie()).is_err());
+
+		let proposal = ban_charlie().encode();
+		let proposal_hash = BlakeTwo256::hash(&proposal);
+		assert_ok!(Democracy::note_preimage(Origin::signed(alice()), proposal));
+		let index =
+			Democracy::internal_start_referendum(proposal_hash, VoteThreshold::SimpleMajority, 1);
+		let vote = Vote { aye: true, conviction: Conviction::None };
+		assert_ok!(Democracy::vote(
+			Origin::signed(alice()),
+			index,
+			AccountVote::Standard { vote, balance: 10 * DOLLARS },
+		));
+
+		// The referendum passes at its end and is enacted by the scheduler a block later.
+		let end = Democracy::referendum_status(index).unwrap().end;
+		System::set_block_number(end);
+		Democracy::on_initialize(end);
+		System::set_block_number(end + 1);
+		Scheduler::on_initialize(end + 1);
+
+		assert!(LaborModule::banned(charlie()).is_some());
+	});
+}
+
+#[test]
+fn sudo_key_is_removed_on_upgrade() {
+	let mut t = new_test_ext(compact_code_unwrap(), false);
+	t.execute_with(|| {
+		put_storage_value(b"Sudo", b"Key", &[], alice());
+
+		RemoveSudoKey::on_runtime_upgrade();
+
+		assert!(!have_storage_value(b"Sudo", b"Key", &[]));
+	});
+}
@@ -56,13 +56,18 @@ pub mod time {
 
 	// NOTE: Currently it is not possible to change the slot duration after the chain has started.
 	//       Attempting to do so will brick block production.
+	//
+	// Only a default: new chains can choose their own through the `pallet_timing` genesis config.
 	pub const SLOT_DURATION: Moment = MILLISECS_PER_BLOCK;
 
 	// 1 in 4 blocks (on average, not counting collisions) will be primary BABE blocks.
+	// Only a default: new chains can choose their own through `BabeConfig::epoch_config`.
 	pub const PRIMARY_PROBABILITY: (u64, u64) = (1, 4);
 
 	// NOTE: Currently it is not possible to change the epoch duration after the chain has started.
 	//       Attempting to do so will brick block production.
+	//
+	// Only a default: new chains can choose their own through the `pallet_timing` genesis config.
 	pub const EPOCH_DURATION_IN_BLOCKS: BlockNumber = 10 * MINUTES;
 	pub const EPOCH_DURATION_IN_SLOTS: u64 = {
 		const SLOT_FILL_RATE: f64 = MILLISECS_PER_BLOCK as f64 / SLOT_DURATION as f64;
@@ -72,6 +72,7 @@ pub use pallet_transaction_payment::{Multiplier, TargetedFeeAdjustment, Currency
 use pallet_session::{historical as pallet_session_historical};
 use sp_inherents::{InherentData, CheckInherentsResult};
 use static_assertions::const_assert;
+#[cfg(feature = "contracts")]
 use pallet_contracts::weights::WeightInfo;
 
 #[cfg(any(feature = "std", test))]
@@ -91,6 +92,9 @@ use impls::Author;
 /// Constant values used within the runtime.
 pub mod constants;
 
+/// Storage migrations of the runtime.
+pub mod migrations;
+
 use constants::{time::*, currency::*};
 use sp_runtime::generic::Era;
 
@@ -282,15 +286,20 @@ impl InstanceFilter<Call> for ProxyType {
 				Call::Vesting(pallet_vesting::Call::vested_transfer(..)) |
 				Call::Indices(pallet_indices::Call::transfer(..))
 			),
-            ProxyType::Governance => matches!(
-				c,
-				Call::Democracy(..) |
-				Call::Council(..) |
-				Call::Society(..) |
-				Call::TechnicalCommittee(..) |
-				Call::Elections(..) |
-				Call::Treasury(..)
-			),
+            ProxyType::Governance => {
+                #[cfg(feature = "society")]
+                if matches!(c, Call::Society(..)) {
+                    return true
+                }
+                matches!(
+					c,
+					Call::Democracy(..) |
+					Call::Council(..) |
+					Call::TechnicalCommittee(..) |
+					Call::Elections(..) |
+					Call::Treasury(..)
+				)
+            },
             ProxyType::Staking => matches!(c, Call::Staking(..)),
         }
     }
@@ -338,11 +347,36 @@ impl pallet_scheduler::Config for Runtime {
 }
 
 parameter_types! {
-	// NOTE: Currently it is not possible to change the epoch duration after the chain has started.
-	//       Attempting to do so will brick block production.
-	pub const EpochDuration: u64 = EPOCH_DURATION_IN_SLOTS;
-	pub const ExpectedBlockTime: Moment = MILLISECS_PER_BLOCK;
-	pub const ReportLongevity: u64 =
+	pub const DefaultSlotDuration: u64 = SLOT_DURATION;
+	pub const DefaultEpochDuration: u64 = EPOCH_DURATION_IN_SLOTS;
+}
+
+/// Slot and epoch durations are chosen at genesis, see `pallet_timing`.
+impl pallet_timing::Config for Runtime {
+    type DefaultSlotDuration = DefaultSlotDuration;
+    type DefaultEpochDuration = DefaultEpochDuration;
+}
+
+// NOTE: Currently it is not possible to change the epoch duration after the chain has started.
+//       Attempting to do so will brick block production.
+pub type EpochDuration = pallet_timing::EpochDurationOf<Runtime>;
+pub type ExpectedBlockTime = pallet_timing::SlotDurationOf<Runtime>;
+
+/// Blocks in `minutes`, at the slot duration chosen at genesis.
+fn minutes(minutes: BlockNumber) -> BlockNumber {
+    pallet_timing::Pallet::<Runtime>::slots_in(minutes as u64 * 60_000) as BlockNumber
+}
+
+fn hours(hours: BlockNumber) -> BlockNumber {
+    minutes(hours * 60)
+}
+
+fn days(days: BlockNumber) -> BlockNumber {
+    hours(days * 24)
+}
+
+parameter_types! {
+	pub ReportLongevity: u64 =
 		BondingDuration::get() as u64 * SessionsPerEra::get() as u64 * EpochDuration::get();
 }
 
@@ -413,14 +447,10 @@ impl pallet_transaction_payment::Config for Runtime {
     TargetedFeeAdjustment<Self, TargetBlockFullness, AdjustmentVariable, MinimumMultiplier>;
 }
 
-parameter_types! {
-	pub const MinimumPeriod: Moment = SLOT_DURATION / 2;
-}
-
 impl pallet_timestamp::Config for Runtime {
     type Moment = Moment;
     type OnTimestampSet = Babe;
-    type MinimumPeriod = MinimumPeriod;
+    type MinimumPeriod = pallet_timing::MinimumPeriodOf<Runtime>;
     type WeightInfo = pallet_timestamp::weights::SubstrateWeight<Runtime>;
 }
 
@@ -514,9 +544,9 @@ impl pallet_staking::Config for Runtime {
 }
 
 parameter_types! {
-	// phase durations. 1/4 of the last session for each.
-	pub const SignedPhase: u32 = EPOCH_DURATION_IN_BLOCKS / 4;
-	pub const UnsignedPhase: u32 = EPOCH_DURATION_IN_BLOCKS / 4;
+	// phase durations. 1/4 of the last session (one epoch, chosen at genesis) for each.
+	pub SignedPhase: BlockNumber = (EpochDuration::get() / 4) as BlockNumber;
+	pub UnsignedPhase: BlockNumber = (EpochDuration::get() / 4) as BlockNumber;
 
 	// fallback: no need to do on-chain phragmen initially.
 	pub const Fallback: pallet_election_provider_multi_phase::FallbackStrategy =
@@ -569,13 +599,13 @@ impl pallet_election_provider_multi_phase::Config for Runtime {
 }
 
 parameter_types! {
-	pub const LaunchPeriod: BlockNumber = 28 * 24 * 60 * MINUTES;
-	pub const VotingPeriod: BlockNumber = 28 * 24 * 60 * MINUTES;
-	pub const FastTrackVotingPeriod: BlockNumber = 3 * 24 * 60 * MINUTES;
+	pub LaunchPeriod: BlockNumber = days(28);
+	pub VotingPeriod: BlockNumber = days(28);
+	pub FastTrackVotingPeriod: BlockNumber = days(3);
 	pub const InstantAllowed: bool = true;
 	pub const MinimumDeposit: Balance = 100 * DOLLARS;
-	pub const EnactmentPeriod: BlockNumber = 30 * 24 * 60 * MINUTES;
-	pub const CooloffPeriod: BlockNumber = 28 * 24 * 60 * MINUTES;
+	pub EnactmentPeriod: BlockNumber = days(30);
+	pub CooloffPeriod: BlockNumber = days(28);
 	// One cent: $10,000 / MB
 	pub const PreimageByteDeposit: Balance = 1 * CENTS;
 	pub const MaxVotes: u32 = 100;
@@ -628,7 +658,7 @@ impl pallet_democracy::Config for Runtime {
 }
 
 parameter_types! {
-	pub const CouncilMotionDuration: BlockNumber = 5 * DAYS;
+	pub CouncilMotionDuration: BlockNumber = days(5);
 	pub const CouncilMaxProposals: u32 = 100;
 	pub const CouncilMaxMembers: u32 = 100;
 }
@@ -652,7 +682,7 @@ parameter_types! {
 	pub const VotingBondBase: Balance = deposit(1, 64);
 	// additional data per vote is 32 bytes (account id).
 	pub const VotingBondFactor: Balance = deposit(0, 32);
-	pub const TermDuration: BlockNumber = 7 * DAYS;
+	pub TermDuration: BlockNumber = days(7);
 	pub const DesiredMembers: u32 = 13;
 	pub const DesiredRunnersUp: u32 = 7;
 	pub const ElectionsPhragmenPalletId: LockIdentifier = *b"phrelect";
@@ -682,7 +712,7 @@ impl pallet_elections_phragmen::Config for Runtime {
 }
 
 parameter_types! {
-	pub const TechnicalMotionDuration: BlockNumber = 5 * DAYS;
+	pub TechnicalMotionDuration: BlockNumber = days(5);
 	pub const TechnicalMaxProposals: u32 = 100;
 	pub const TechnicalMaxMembers: u32 = 100;
 }
@@ -722,16 +752,16 @@ impl pallet_membership::Config<pallet_membership::Instance1> for Runtime {
 parameter_types! {
 	pub const ProposalBond: Permill = Permill::from_percent(5);
 	pub const ProposalBondMinimum: Balance = 1 * DOLLARS;
-	pub const SpendPeriod: BlockNumber = 1 * DAYS;
+	pub SpendPeriod: BlockNumber = days(1);
 	pub const Burn: Permill = Permill::from_percent(50);
-	pub const TipCountdown: BlockNumber = 1 * DAYS;
+	pub TipCountdown: BlockNumber = days(1);
 	pub const TipFindersFee: Percent = Percent::from_percent(20);
 	pub const TipReportDepositBase: Balance = 1 * DOLLARS;
 	pub const DataDepositPerByte: Balance = 1 * CENTS;
 	pub const BountyDepositBase: Balance = 1 * DOLLARS;
-	pub const BountyDepositPayoutDelay: BlockNumber = 1 * DAYS;
+	pub BountyDepositPayoutDelay: BlockNumber = days(1);
 	pub const TreasuryPalletId: PalletId = PalletId(*b"py/trsry");
-	pub const BountyUpdatePeriod: BlockNumber = 14 * DAYS;
+	pub BountyUpdatePeriod: BlockNumber = days(14);
 	pub const MaximumReasonLength: u32 = 16384;
 	pub const BountyCuratorDeposit: Permill = Permill::from_percent(50);
 	pub const BountyValueMinimum: Balance = 5 * DOLLARS;
@@ -786,6 +816,7 @@ impl pallet_tips::Config for Runtime {
     type WeightInfo = pallet_tips::weights::SubstrateWeight<Runtime>;
 }
 
+#[cfg(feature = "contracts")]
 parameter_types! {
 	pub TombstoneDeposit: Balance = deposit(
 		1,
@@ -794,7 +825,7 @@ parameter_types! {
 	pub DepositPerContract: Balance = TombstoneDeposit::get();
 	pub const DepositPerStorageByte: Balance = deposit(0, 1);
 	pub const DepositPerStorageItem: Balance = deposit(1, 0);
-	pub RentFraction: Perbill = Perbill::from_rational(1u32, 30 * DAYS);
+	pub RentFraction: Perbill = Perbill::from_rational(1u32, days(30));
 	pub const SurchargeReward: Balance = 150 * MILLICENTS;
 	pub const SignedClaimHandicap: u32 = 2;
 	pub const MaxDepth: u32 = 32;
@@ -811,6 +842,7 @@ parameter_types! {
 	pub MaxCodeSize: u32 = 128 * 1024;
 }
 
+#[cfg(feature = "contracts")]
 impl pallet_contracts::Config for Runtime {
     type Time = Timestamp;
     type Randomness = RandomnessCollectiveFlip;
@@ -834,6 +866,7 @@ impl pallet_contracts::Config for Runtime {
     type MaxCodeSize = MaxCodeSize;
 }
 
+#[cfg(feature = "sudo")]
 impl pallet_sudo::Config for Runtime {
     type Event = Event;
     type Call = Call;
@@ -867,15 +900,7 @@ impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for R
             // so the actual block number is `n`.
             .saturating_sub(1);
         let era = Era::mortal(period, current_block);
-        let extra = (
-            frame_system::CheckSpecVersion::<Runtime>::new(),
-            frame_system::CheckTxVersion::<Runtime>::new(),
-            frame_system::CheckGenesis::<Runtime>::new(),
-            frame_system::CheckEra::<Runtime>::from(era),
-            frame_system::CheckNonce::<Runtime>::from(nonce),
-            frame_system::CheckWeight::<Runtime>::new(),
-            pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
-        );
+        let extra = signed_extra(nonce, tip, era);
         let raw_payload = SignedPayload::new(call, extra)
             .map_err(|e| {
                 log::warn!("Unable to create signed payload: {:?}", e);
@@ -971,6 +996,7 @@ impl pallet_identity::Config for Runtime {
     type WeightInfo = pallet_identity::weights::SubstrateWeight<Runtime>;
 }
 
+#[cfg(feature = "recovery")]
 parameter_types! {
 	pub const ConfigDepositBase: Balance = 5 * DOLLARS;
 	pub const FriendDepositFactor: Balance = 50 * CENTS;
@@ -978,6 +1004,7 @@ parameter_types! {
 	pub const RecoveryDeposit: Balance = 5 * DOLLARS;
 }
 
+#[cfg(feature = "recovery")]
 impl pallet_recovery::Config for Runtime {
     type Event = Event;
     type Call = Call;
@@ -988,18 +1015,20 @@ impl pallet_recovery::Config for Runtime {
     type RecoveryDeposit = RecoveryDeposit;
 }
 
+#[cfg(feature = "society")]
 parameter_types! {
 	pub const CandidateDeposit: Balance = 10 * DOLLARS;
 	pub const WrongSideDeduction: Balance = 2 * DOLLARS;
 	pub const MaxStrikes: u32 = 10;
-	pub const RotationPeriod: BlockNumber = 80 * HOURS;
+	pub RotationPeriod: BlockNumber = hours(80);
 	pub const PeriodSpend: Balance = 500 * DOLLARS;
-	pub const MaxLockDuration: BlockNumber = 36 * 30 * DAYS;
-	pub const ChallengePeriod: BlockNumber = 7 * DAYS;
+	pub MaxLockDuration: BlockNumber = days(36 * 30);
+	pub ChallengePeriod: BlockNumber = days(7);
 	pub const MaxCandidateIntake: u32 = 10;
 	pub const SocietyPalletId: PalletId = PalletId(*b"py/socie");
 }
 
+#[cfg(feature = "society")]
 impl pallet_society::Config for Runtime {
     type Event = Event;
     type PalletId = SocietyPalletId;
@@ -1034,17 +1063,35 @@ impl pallet_mmr::Config for Runtime {
     const INDEXING_PREFIX: &'static [u8] = b"mmr";
     type Hashing = <Runtime as frame_system::Config>::Hashing;
     type Hash = <Runtime as frame_system::Config>::Hash;
-    type LeafData = frame_system::Pallet<Self>;
+    type LeafData = LaborMmrLeaf;
     type OnNewRoot = ();
     type WeightInfo = ();
 }
 
+/// Provides the MMR leaf of every block: the parent hash, as `frame_system::Pallet` does, and the
+/// jobs that the parent block completed.
+pub struct LaborMmrLeaf;
+
+impl pallet_mmr::primitives::LeafDataProvider for LaborMmrLeaf {
+    type LeafData = pallet_labor::CompletionLeafOf<Runtime>;
+
+    fn leaf_data() -> Self::LeafData {
+        let parent = System::block_number().saturating_sub(1);
+        pallet_labor::CompletionLeaf {
+            parent_hash: System::parent_hash(),
+            completions: LaborModule::take_completions(parent),
+        }
+    }
+}
+
+#[cfg(feature = "lottery")]
 parameter_types! {
 	pub const LotteryPalletId: PalletId = PalletId(*b"py/lotto");
 	pub const MaxCalls: usize = 10;
 	pub const MaxGenerateRandom: u32 = 10;
 }
 
+#[cfg(feature = "lottery")]
 impl pallet_lottery::Config for Runtime {
     type PalletId = LotteryPalletId;
     type Call = Call;
@@ -1082,17 +1129,19 @@ impl pallet_assets::Config for Runtime {
     type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
 }
 
+#[cfg(feature = "gilt")]
 parameter_types! {
 	pub IgnoredIssuance: Balance = Treasury::pot();
 	pub const QueueCount: u32 = 300;
 	pub const MaxQueueLen: u32 = 1000;
 	pub const FifoQueueLen: u32 = 500;
-	pub const Period: BlockNumber = 30 * DAYS;
+	pub Period: BlockNumber = days(30);
 	pub const MinFreeze: Balance = 100 * DOLLARS;
 	pub const IntakePeriod: BlockNumber = 10;
 	pub const MaxIntakeBids: u32 = 10;
 }
 
+#[cfg(feature = "gilt")]
 impl pallet_gilt::Config for Runtime {
     type Event = Event;
     type Currency = Balances;
@@ -1111,58 +1160,268 @@ impl pallet_gilt::Config for Runtime {
     type WeightInfo = pallet_gilt::weights::SubstrateWeight<Runtime>;
 }
 
+parameter_types! {
+	pub const LaborMaxDetailsLen: u32 = 1024;
+	pub const LaborReferralCommission: Perbill = Perbill::from_percent(5);
+	pub const LaborMaxSealedKeyLen: u32 = 128;
+}
+
 /// Configure the pallet-labor in pallets/labor.
 impl pallet_labor::Config for Runtime {
     type Event = Event;
+    type Currency = Balances;
+    type AdminOrigin = EnsureRoot<AccountId>;
+    type MaxDetailsLen = LaborMaxDetailsLen;
+    type ReferralCommission = LaborReferralCommission;
+    type Call = Call;
+    type PalletsOrigin = OriginCaller;
+    type Scheduler = Scheduler;
+    type MaxSealedKeyLen = LaborMaxSealedKeyLen;
+}
+
+// `construct_runtime!` does not support `#[cfg]` on pallets. The pallets are given as groups split
+// at the optional pallets. Every optional pallet has a macro that appends it to the pallet list if
+// its feature is enabled, then appends the next group and passes the list on to the next macro, the
+// last of which constructs the runtime. The pallets keep their order, which is the order of their
+// hooks and genesis builds, and their explicit indices, so leaving out a pallet does not shift the
+// others.
+#[cfg(feature = "contracts")]
+macro_rules! with_contracts {
+	(
+		[$next:ident $(, $rest:ident)*] { $($pallets:tt)* } { $($group:tt)* } $($groups:tt)*
+	) => {
+		$next! {
+			[$($rest),*]
+			{ $($pallets)* Contracts: pallet_contracts::{Pallet, Call, Config<T>, Storage, Event<T>} = 18, $($group)* }
+			$($groups)*
+		}
+	};
+}
+#[cfg(not(feature = "contracts"))]
+macro_rules! with_contracts {
+	(
+		[$next:ident $(, $rest:ident)*] { $($pallets:tt)* } { $($group:tt)* } $($groups:tt)*
+	) => {
+		$next! { [$($rest),*] { $($pallets)* $($group)* } $($groups)* }
+	};
+}
+
+#[cfg(feature = "sudo")]
+macro_rules! with_sudo {
+	(
+		[$next:ident $(, $rest:ident)*] { $($pallets:tt)* } { $($group:tt)* } $($groups:tt)*
+	) => {
+		$next! {
+			[$($rest),*]
+			{ $($pallets)* Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>} = 19, $($group)* }
+			$($groups)*
+		}
+	};
+}
+#[cfg(not(feature = "sudo"))]
+macro_rules! with_sudo {
+	(
+		[$next:ident $(, $rest:ident)*] { $($pallets:tt)* } { $($group:tt)* } $($groups:tt)*
+	) => {
+		$next! { [$($rest),*] { $($pallets)* $($group)* } $($groups)* }
+	};
+}
+
+#[cfg(feature = "society")]
+macro_rules! with_society {
+	(
+		[$next:ident $(, $rest:ident)*] { $($pallets:tt)* } { $($group:tt)* } $($groups:tt)*
+	) => {
+		$next! {
+			[$($rest),*]
+			{ $($pallets)* Society: pallet_society::{Pallet, Call, Storage, Event<T>, Config<T>} = 26, $($group)* }
+			$($groups)*
+		}
+	};
+}
+#[cfg(not(feature = "society"))]
+macro_rules! with_society {
+	(
+		[$next:ident $(, $rest:ident)*] { $($pallets:tt)* } { $($group:tt)* } $($groups:tt)*
+	) => {
+		$next! { [$($rest),*] { $($pallets)* $($group)* } $($groups)* }
+	};
+}
+
+#[cfg(feature = "recovery")]
+macro_rules! with_recovery {
+	(
+		[$next:ident $(, $rest:ident)*] { $($pallets:tt)* } { $($group:tt)* } $($groups:tt)*
+	) => {
+		$next! {
+			[$($rest),*]
+			{ $($pallets)* Recovery: pallet_recovery::{Pallet, Call, Storage, Event<T>} = 27, $($group)* }
+			$($groups)*
+		}
+	};
+}
+#[cfg(not(feature = "recovery"))]
+macro_rules! with_recovery {
+	(
+		[$next:ident $(, $rest:ident)*] { $($pallets:tt)* } { $($group:tt)* } $($groups:tt)*
+	) => {
+		$next! { [$($rest),*] { $($pallets)* $($group)* } $($groups)* }
+	};
+}
+
+#[cfg(feature = "lottery")]
+macro_rules! with_lottery {
+	(
+		[$next:ident $(, $rest:ident)*] { $($pallets:tt)* } { $($group:tt)* } $($groups:tt)*
+	) => {
+		$next! {
+			[$($rest),*]
+			{ $($pallets)* Lottery: pallet_lottery::{Pallet, Call, Storage, Event<T>} = 36, $($group)* }
+			$($groups)*
+		}
+	};
+}
+#[cfg(not(feature = "lottery"))]
+macro_rules! with_lottery {
+	(
+		[$next:ident $(, $rest:ident)*] { $($pallets:tt)* } { $($group:tt)* } $($groups:tt)*
+	) => {
+		$next! { [$($rest),*] { $($pallets)* $($group)* } $($groups)* }
+	};
+}
+
+#[cfg(feature = "gilt")]
+macro_rules! with_gilt {
+	(
+		[$next:ident $(, $rest:ident)*] { $($pallets:tt)* } { $($group:tt)* } $($groups:tt)*
+	) => {
+		$next! {
+			[$($rest),*]
+			{ $($pallets)* Gilt: pallet_gilt::{Pallet, Call, Storage, Event<T>, Config} = 37, $($group)* }
+			$($groups)*
+		}
+	};
+}
+#[cfg(not(feature = "gilt"))]
+macro_rules! with_gilt {
+	(
+		[$next:ident $(, $rest:ident)*] { $($pallets:tt)* } { $($group:tt)* } $($groups:tt)*
+	) => {
+		$next! { [$($rest),*] { $($pallets)* $($group)* } $($groups)* }
+	};
+}
+
+macro_rules! construct_labor_runtime {
+	([] { $($pallets:tt)* }) => {
+		construct_runtime!(
+			pub enum Runtime where
+				Block = Block,
+				NodeBlock = node_primitives::Block,
+				UncheckedExtrinsic = UncheckedExtrinsic
+			{
+				$($pallets)*
+			}
+		);
+	};
 }
 
-construct_runtime!(
-	pub enum Runtime where
-		Block = Block,
-		NodeBlock = node_primitives::Block,
-		UncheckedExtrinsic = UncheckedExtrinsic
+with_contracts! {
+	[with_sudo, with_society, with_recovery, with_lottery, with_gilt, construct_labor_runtime]
 	{
-		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		Utility: pallet_utility::{Pallet, Call, Event},
-		Babe: pallet_babe::{Pallet, Call, Storage, Config, ValidateUnsigned},
-		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
-		Authorship: pallet_authorship::{Pallet, Call, Storage, Inherent},
-		Indices: pallet_indices::{Pallet, Call, Storage, Config<T>, Event<T>},
-		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
-		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
-		ElectionProviderMultiPhase: pallet_election_provider_multi_phase::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
-		Staking: pallet_staking::{Pallet, Call, Config<T>, Storage, Event<T>},
-		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
-		Democracy: pallet_democracy::{Pallet, Call, Storage, Config, Event<T>},
-		Council: pallet_collective::<Instance1>::{Pallet, Call, Storage, Origin<T>, Event<T>, Config<T>},
-		TechnicalCommittee: pallet_collective::<Instance2>::{Pallet, Call, Storage, Origin<T>, Event<T>, Config<T>},
-		Elections: pallet_elections_phragmen::{Pallet, Call, Storage, Event<T>, Config<T>},
-		TechnicalMembership: pallet_membership::<Instance1>::{Pallet, Call, Storage, Event<T>, Config<T>},
-		Grandpa: pallet_grandpa::{Pallet, Call, Storage, Config, Event, ValidateUnsigned},
-		Treasury: pallet_treasury::{Pallet, Call, Storage, Config, Event<T>},
-		Contracts: pallet_contracts::{Pallet, Call, Config<T>, Storage, Event<T>},
-		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
-		ImOnline: pallet_im_online::{Pallet, Call, Storage, Event<T>, ValidateUnsigned, Config<T>},
-		AuthorityDiscovery: pallet_authority_discovery::{Pallet, Call, Config},
-		Offences: pallet_offences::{Pallet, Call, Storage, Event},
-		Historical: pallet_session_historical::{Pallet},
-		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Call, Storage},
-		Identity: pallet_identity::{Pallet, Call, Storage, Event<T>},
-		Society: pallet_society::{Pallet, Call, Storage, Event<T>, Config<T>},
-		Recovery: pallet_recovery::{Pallet, Call, Storage, Event<T>},
-		Vesting: pallet_vesting::{Pallet, Call, Storage, Event<T>, Config<T>},
-		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>},
-		Proxy: pallet_proxy::{Pallet, Call, Storage, Event<T>},
-		Multisig: pallet_multisig::{Pallet, Call, Storage, Event<T>},
-		Bounties: pallet_bounties::{Pallet, Call, Storage, Event<T>},
-		Tips: pallet_tips::{Pallet, Call, Storage, Event<T>},
-		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
-		Mmr: pallet_mmr::{Pallet, Storage},
-		Lottery: pallet_lottery::{Pallet, Call, Storage, Event<T>},
-		Gilt: pallet_gilt::{Pallet, Call, Storage, Event<T>, Config},
-		LaborModule: pallet_labor::{Pallet, Call, Storage, Event<T>},
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>} = 0,
+		Utility: pallet_utility::{Pallet, Call, Event} = 1,
+		Babe: pallet_babe::{Pallet, Call, Storage, Config, ValidateUnsigned} = 2,
+		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent} = 3,
+		Authorship: pallet_authorship::{Pallet, Call, Storage, Inherent} = 4,
+		Indices: pallet_indices::{Pallet, Call, Storage, Config<T>, Event<T>} = 5,
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>} = 6,
+		TransactionPayment: pallet_transaction_payment::{Pallet, Storage} = 7,
+		ElectionProviderMultiPhase: pallet_election_provider_multi_phase::{Pallet, Call, Storage, Event<T>, ValidateUnsigned} = 8,
+		Staking: pallet_staking::{Pallet, Call, Config<T>, Storage, Event<T>} = 9,
+		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>} = 10,
+		Democracy: pallet_democracy::{Pallet, Call, Storage, Config, Event<T>} = 11,
+		Council: pallet_collective::<Instance1>::{Pallet, Call, Storage, Origin<T>, Event<T>, Config<T>} = 12,
+		TechnicalCommittee: pallet_collective::<Instance2>::{Pallet, Call, Storage, Origin<T>, Event<T>, Config<T>} = 13,
+		Elections: pallet_elections_phragmen::{Pallet, Call, Storage, Event<T>, Config<T>} = 14,
+		TechnicalMembership: pallet_membership::<Instance1>::{Pallet, Call, Storage, Event<T>, Config<T>} = 15,
+		Grandpa: pallet_grandpa::{Pallet, Call, Storage, Config, Event, ValidateUnsigned} = 16,
+		Treasury: pallet_treasury::{Pallet, Call, Storage, Config, Event<T>} = 17,
 	}
-);
+	{}
+	{
+		ImOnline: pallet_im_online::{Pallet, Call, Storage, Event<T>, ValidateUnsigned, Config<T>} = 20,
+		AuthorityDiscovery: pallet_authority_discovery::{Pallet, Call, Config} = 21,
+		Offences: pallet_offences::{Pallet, Call, Storage, Event} = 22,
+		Historical: pallet_session_historical::{Pallet} = 23,
+		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Call, Storage} = 24,
+		Identity: pallet_identity::{Pallet, Call, Storage, Event<T>} = 25,
+	}
+	{}
+	{
+		Vesting: pallet_vesting::{Pallet, Call, Storage, Event<T>, Config<T>} = 28,
+		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>} = 29,
+		Proxy: pallet_proxy::{Pallet, Call, Storage, Event<T>} = 30,
+		Multisig: pallet_multisig::{Pallet, Call, Storage, Event<T>} = 31,
+		Bounties: pallet_bounties::{Pallet, Call, Storage, Event<T>} = 32,
+		Tips: pallet_tips::{Pallet, Call, Storage, Event<T>} = 33,
+		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>} = 34,
+		Mmr: pallet_mmr::{Pallet, Storage} = 35,
+	}
+	{}
+	{
+		LaborModule: pallet_labor::{Pallet, Call, Storage, Event<T>} = 38,
+		Timing: pallet_timing::{Pallet, Storage, Config} = 39,
+	}
+}
+
+/// Stands in for the contracts pallet in the contracts runtime API if the pallet is not part of
+/// the runtime, which the RPC server of the node requires regardless.
+#[cfg(not(feature = "contracts"))]
+struct Contracts;
+
+#[cfg(not(feature = "contracts"))]
+impl Contracts {
+    fn bare_call(
+        _origin: AccountId,
+        _dest: AccountId,
+        _value: Balance,
+        _gas_limit: u64,
+        _input_data: Vec<u8>,
+    ) -> pallet_contracts_primitives::ContractExecResult {
+        pallet_contracts_primitives::ContractResult {
+            exec_result: Err(sp_runtime::DispatchError::Other("Contracts are disabled")),
+            gas_consumed: 0,
+        }
+    }
+
+    fn bare_instantiate(
+        _origin: AccountId,
+        _endowment: Balance,
+        _gas_limit: u64,
+        _code: pallet_contracts_primitives::Code<Hash>,
+        _data: Vec<u8>,
+        _salt: Vec<u8>,
+        _compute_projection: bool,
+    ) -> pallet_contracts_primitives::ContractInstantiateResult<AccountId, BlockNumber> {
+        pallet_contracts_primitives::ContractResult {
+            exec_result: Err(sp_runtime::DispatchError::Other("Contracts are disabled")),
+            gas_consumed: 0,
+        }
+    }
+
+    fn get_storage(
+        _address: AccountId,
+        _key: [u8; 32],
+    ) -> pallet_contracts_primitives::GetStorageResult {
+        Err(pallet_contracts_primitives::ContractAccessError::DoesntExist)
+    }
+
+    fn rent_projection(
+        _address: AccountId,
+    ) -> pallet_contracts_primitives::RentProjectionResult<BlockNumber> {
+        Err(pallet_contracts_primitives::ContractAccessError::DoesntExist)
+    }
+}
 
 /// The address format for describing accounts.
 pub type Address = sp_runtime::MultiAddress<AccountId, AccountIndex>;
@@ -1188,6 +1447,18 @@ pub type SignedExtra = (
     frame_system::CheckWeight<Runtime>,
     pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
 );
+/// The `SignedExtra` of an extrinsic signed with `nonce` and valid in `era`, paying `tip`.
+pub fn signed_extra(nonce: Index, tip: Balance, era: Era) -> SignedExtra {
+    (
+        frame_system::CheckSpecVersion::<Runtime>::new(),
+        frame_system::CheckTxVersion::<Runtime>::new(),
+        frame_system::CheckGenesis::<Runtime>::new(),
+        frame_system::CheckEra::<Runtime>::from(era),
+        frame_system::CheckNonce::<Runtime>::from(nonce),
+        frame_system::CheckWeight::<Runtime>::new(),
+        pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+    )
+}
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
 /// The payload being signed in transactions.
@@ -1201,7 +1472,7 @@ pub type Executive = frame_executive::Executive<
     frame_system::ChainContext<Runtime>,
     Runtime,
     AllPallets,
-    (),
+    migrations::Migrations,
 >;
 
 /// MMR helper types.
@@ -1315,13 +1586,16 @@ impl_runtime_apis! {
 			// slot duration and expected target block time, for safely
 			// resisting network delays of maximum two seconds.
 			// <https://research.web3.foundation/en/latest/polkadot/BABE/Babe/#6-practical-results>
+			//
+			// Chains may choose their own `c` at genesis through `BabeConfig::epoch_config`.
+			let epoch_config = Babe::epoch_config().unwrap_or(BABE_GENESIS_EPOCH_CONFIG);
 			sp_consensus_babe::BabeGenesisConfiguration {
 				slot_duration: Babe::slot_duration(),
 				epoch_length: EpochDuration::get(),
-				c: BABE_GENESIS_EPOCH_CONFIG.c,
+				c: epoch_config.c,
 				genesis_authorities: Babe::authorities(),
 				randomness: Babe::randomness(),
-				allowed_slots: BABE_GENESIS_EPOCH_CONFIG.allowed_slots,
+				allowed_slots: epoch_config.allowed_slots,
 			}
 		}
 
@@ -1517,15 +1791,18 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, pallet_balances, Balances);
 			add_benchmark!(params, batches, pallet_bounties, Bounties);
 			add_benchmark!(params, batches, pallet_collective, Council);
+			#[cfg(feature = "contracts")]
 			add_benchmark!(params, batches, pallet_contracts, Contracts);
 			add_benchmark!(params, batches, pallet_democracy, Democracy);
 			add_benchmark!(params, batches, pallet_election_provider_multi_phase, ElectionProviderMultiPhase);
 			add_benchmark!(params, batches, pallet_elections_phragmen, Elections);
+			#[cfg(feature = "gilt")]
 			add_benchmark!(params, batches, pallet_gilt, Gilt);
 			add_benchmark!(params, batches, pallet_grandpa, Grandpa);
 			add_benchmark!(params, batches, pallet_identity, Identity);
 			add_benchmark!(params, batches, pallet_im_online, ImOnline);
 			add_benchmark!(params, batches, pallet_indices, Indices);
+			#[cfg(feature = "lottery")]
 			add_benchmark!(params, batches, pallet_lottery, Lottery);
 			add_benchmark!(params, batches, pallet_membership, TechnicalMembership);
 			add_benchmark!(params, batches, pallet_mmr, Mmr);
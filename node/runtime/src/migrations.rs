@@ -0,0 +1,44 @@
+//! Storage migrations run by `Executive` on runtime upgrades.
+
+use frame_support::{
+	storage::migration::{have_storage_value, take_storage_value},
+	traits::{Get, OnRuntimeUpgrade},
+	weights::Weight,
+};
+use node_primitives::AccountId;
+use crate::Runtime;
+
+/// The migrations of this build of the runtime.
+#[cfg(feature = "sudo")]
+pub type Migrations = ();
+
+/// The migrations of this build of the runtime.
+#[cfg(not(feature = "sudo"))]
+pub type Migrations = RemoveSudoKey;
+
+/// Removes the key of `pallet_sudo`, handing root over to governance.
+///
+/// A runtime built without the `sudo` feature runs this on upgrade, so the key a chain was
+/// launched with does not outlive the pallet. Enacting such an upgrade through Democracy is the
+/// last use of root that does not go through governance.
+pub struct RemoveSudoKey;
+
+impl OnRuntimeUpgrade for RemoveSudoKey {
+	fn on_runtime_upgrade() -> Weight {
+		let db = <Runtime as frame_system::Config>::DbWeight::get();
+		if !have_storage_value(b"Sudo", b"Key", &[]) {
+			return db.reads(1)
+		}
+		let key = take_storage_value::<AccountId>(b"Sudo", b"Key", &[]);
+		log::info!(target: "runtime", "Removed the sudo key {:?}", key);
+		db.reads_writes(1, 1)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		if have_storage_value(b"Sudo", b"Key", &[]) {
+			return Err("The sudo key is still set")
+		}
+		Ok(())
+	}
+}
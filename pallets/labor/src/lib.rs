@@ -17,17 +17,61 @@ mod benchmarking;
 
 #[frame_support::pallet]
 pub mod pallet {
-    use frame_support::{dispatch::DispatchResult, pallet_prelude::*};
+    use frame_support::{
+        dispatch::{DispatchResult, Dispatchable}, pallet_prelude::*, transactional,
+        traits::{
+            BalanceStatus, Currency, ReservableCurrency,
+            schedule::{DispatchTime, Named, LOWEST_PRIORITY},
+        },
+        sp_runtime::{Perbill, traits::{Saturating, Zero}},
+    };
     use frame_system::pallet_prelude::*;
     use frame_support::inherent::Vec;
     use scale_info::TypeInfo;
     use codec::{Decode, Encode};
 
+    /// Identifier of a job posted through this pallet.
+    pub type JobId = u32;
+
+    /// Identifier of a series of recurring job postings.
+    pub type SeriesId = u32;
+
+    pub type BalanceOf<T> =
+        <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
     /// Configure the pallet by specifying the parameters and types on which it depends.
     #[pallet::config]
     pub trait Config: frame_system::Config {
         /// Because this pallet emits events, it depends on the runtime's definition of an event.
         type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// The currency in which job payments are escrowed.
+        type Currency: ReservableCurrency<Self::AccountId>;
+
+        /// Origin allowed to ban accounts, freeze jobs and resolve disputes.
+        type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+        /// Maximum length of the details of a job, in bytes.
+        #[pallet::constant]
+        type MaxDetailsLen: Get<u32>;
+
+        /// Share of the payout that goes to the referrer of the assigned worker. The value in
+        /// effect when a job is posted is recorded on the job.
+        #[pallet::constant]
+        type ReferralCommission: Get<Perbill>;
+
+        /// The overarching call type, used to schedule reposts of recurring jobs.
+        type Call: Parameter + Dispatchable<Origin = Self::Origin> + From<Call<Self>>;
+
+        /// The caller origin, overarching type of all pallets origins.
+        type PalletsOrigin: From<frame_system::RawOrigin<Self::AccountId>>;
+
+        /// The scheduler that reposts recurring jobs.
+        type Scheduler: Named<Self::BlockNumber, <Self as Config>::Call, Self::PalletsOrigin>;
+
+        /// Maximum length of a sealed content key, in bytes.
+        #[pallet::constant]
+        type MaxSealedKeyLen: Get<u32>;
     }
 
     #[pallet::pallet]
@@ -36,8 +80,8 @@ pub mod pallet {
 
     #[derive(Debug, Encode, Decode, Clone, TypeInfo, PartialEq)]
     pub struct StructValue {
-        number: u8,
-        string: Vec<u8>,
+        pub number: u8,
+        pub string: Vec<u8>,
     }
 
     #[derive(Debug, Encode, Decode, Clone, TypeInfo, PartialEq)]
@@ -47,6 +91,83 @@ pub mod pallet {
         Value3,
     }
 
+    /// Lifecycle of a job, from posting until its escrow has been paid out or refunded.
+    #[derive(Debug, Encode, Decode, Clone, Copy, TypeInfo, PartialEq)]
+    pub enum JobStatus {
+        /// Accepting applications; the payment is reserved from the employer.
+        Open,
+        /// A worker has been assigned and is doing the work.
+        Assigned,
+        /// The employer approved the work and the payment went to the worker.
+        Completed,
+        /// The employer withdrew the job before assigning anyone.
+        Cancelled,
+        /// One of the parties disputed the job; awaiting the admin origin.
+        Disputed,
+        /// The admin origin settled the dispute.
+        Resolved,
+    }
+
+    #[derive(Debug, Encode, Decode, Clone, TypeInfo, PartialEq)]
+    pub struct Job<AccountId, Balance> {
+        pub employer: AccountId,
+        pub worker: Option<AccountId>,
+        pub payment: Balance,
+        /// Collateral reserved from the worker when they are assigned.
+        pub worker_bond: Balance,
+        /// Account that referred the assigned worker, if any.
+        pub referrer: Option<AccountId>,
+        /// Share of the payment owed to the referrer.
+        pub referral_commission: Perbill,
+        pub status: JobStatus,
+        pub details: Vec<u8>,
+    }
+
+    pub type JobOf<T> = Job<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+    /// Template of a job that is reposted every `period` blocks.
+    #[derive(Debug, Encode, Decode, Clone, TypeInfo, PartialEq)]
+    pub struct RecurringJob<AccountId, Balance, BlockNumber> {
+        pub employer: AccountId,
+        pub details: Vec<u8>,
+        pub payment: Balance,
+        pub worker_bond: Balance,
+        pub period: BlockNumber,
+    }
+
+    /// A job whose payment went to its worker, on approval or by resolving a dispute.
+    #[derive(Debug, Encode, Decode, Clone, TypeInfo, PartialEq)]
+    pub struct Completion<AccountId, Balance> {
+        pub job: JobId,
+        pub worker: AccountId,
+        /// The payment the worker received, after the referral commission.
+        pub payment: Balance,
+    }
+
+    pub type CompletionOf<T> = Completion<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+    /// The MMR leaf of a block, committing to the parent block and the jobs it completed.
+    ///
+    /// A worker proves that they completed a job with the leaf and its MMR proof, which anyone
+    /// holding the MMR root can check without access to the chain.
+    #[derive(Debug, Encode, Decode, Clone, TypeInfo, PartialEq)]
+    pub struct CompletionLeaf<Hash, AccountId, Balance> {
+        pub parent_hash: Hash,
+        pub completions: Vec<Completion<AccountId, Balance>>,
+    }
+
+    pub type CompletionLeafOf<T> = CompletionLeaf<
+        <T as frame_system::Config>::Hash,
+        <T as frame_system::Config>::AccountId,
+        BalanceOf<T>,
+    >;
+
+    pub type RecurringJobOf<T> = RecurringJob<
+        <T as frame_system::Config>::AccountId,
+        BalanceOf<T>,
+        <T as frame_system::Config>::BlockNumber,
+    >;
+
     #[pallet::storage]
     #[pallet::getter(fn some_number)]
     pub type SomeNumber<T> = StorageValue<_, u32>;
@@ -67,11 +188,73 @@ pub mod pallet {
     #[pallet::getter(fn some_map)]
     pub type SomeMap<T> = StorageMap<_, Blake2_256, u32, StructValue>;
 
+    #[pallet::storage]
+    #[pallet::getter(fn next_job_id)]
+    pub type NextJobId<T> = StorageValue<_, JobId, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn jobs)]
+    pub type Jobs<T: Config> = StorageMap<_, Blake2_128Concat, JobId, JobOf<T>>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn next_series_id)]
+    pub type NextSeriesId<T> = StorageValue<_, SeriesId, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn recurring_jobs)]
+    pub type RecurringJobs<T: Config> =
+        StorageMap<_, Blake2_128Concat, SeriesId, RecurringJobOf<T>>;
+
+    /// The block of the scheduled repost of a recurring job series.
+    #[pallet::storage]
+    #[pallet::getter(fn next_repost)]
+    pub type NextRepost<T: Config> = StorageMap<_, Blake2_128Concat, SeriesId, T::BlockNumber>;
+
+    /// Workers that applied to a job, with the account that referred them.
+    #[pallet::storage]
+    #[pallet::getter(fn applications)]
+    pub type Applications<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, JobId, Blake2_128Concat, T::AccountId, Option<T::AccountId>,
+    >;
+
+    /// Hash of the encrypted payload of private jobs. Their public `details` are left empty.
+    /// Removed once the job is settled.
+    #[pallet::storage]
+    #[pallet::getter(fn sealed_details)]
+    pub type SealedDetails<T: Config> = StorageMap<_, Blake2_128Concat, JobId, T::Hash>;
+
+    /// Content key of a private job, sealed by the employer to the assigned worker's key.
+    /// Removed once the job is settled, by which time the worker has read it.
+    #[pallet::storage]
+    #[pallet::getter(fn content_keys)]
+    pub type ContentKeys<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, JobId, Blake2_128Concat, T::AccountId, Vec<u8>>;
+
+    /// Accounts that may no longer post or apply for jobs.
+    #[pallet::storage]
+    #[pallet::getter(fn banned)]
+    pub type Banned<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
+
+    /// Jobs whose escrow may not move until they are unfrozen or resolved.
+    #[pallet::storage]
+    #[pallet::getter(fn frozen)]
+    pub type Frozen<T> = StorageMap<_, Blake2_128Concat, JobId, ()>;
+
+    /// The jobs completed in a block, kept until the MMR leaf of its child takes them.
+    #[pallet::storage]
+    #[pallet::getter(fn completions_in)]
+    pub type Completions<T: Config> =
+        StorageMap<_, Twox64Concat, T::BlockNumber, Vec<CompletionOf<T>>, ValueQuery>;
+
+    /// The block in which a job was completed.
+    #[pallet::storage]
+    #[pallet::getter(fn completed_at)]
+    pub type CompletedAt<T: Config> = StorageMap<_, Blake2_128Concat, JobId, T::BlockNumber>;
 
     // Pallets use events to inform users when important changes are made.
     // https://substrate.dev/docs/en/knowledgebase/runtime/events
     #[pallet::event]
-    #[pallet::metadata(T::AccountId = "AccountId")]
+    #[pallet::metadata(T::AccountId = "AccountId", BalanceOf<T> = "Balance")]
     #[pallet::generate_deposit(pub (super) fn deposit_event)]
     pub enum Event<T: Config> {
         /// Event documentation should end with an array that provides descriptive names for event
@@ -81,6 +264,42 @@ pub mod pallet {
         SomeStructStored(StructValue, T::AccountId),
         SomeEnumStored(Enum, T::AccountId),
         SomeMapStored(u32, StructValue, T::AccountId),
+        /// A job was posted and its payment reserved. [job, employer, payment]
+        JobPosted(JobId, T::AccountId, BalanceOf<T>),
+        /// A worker applied for a job. [job, worker]
+        JobApplied(JobId, T::AccountId),
+        /// A worker was assigned to a job. [job, worker]
+        JobAssigned(JobId, T::AccountId),
+        /// The employer approved the work and the payment was released. [job, worker, payment]
+        JobCompleted(JobId, T::AccountId, BalanceOf<T>),
+        /// An open job was withdrawn and its payment refunded. [job]
+        JobCancelled(JobId),
+        /// A party disputed an assigned job. [job, who]
+        JobDisputed(JobId, T::AccountId),
+        /// The admin origin settled a dispute. [job, paid to worker]
+        JobResolved(JobId, bool),
+        /// An account was banned from the job market. [who]
+        AccountBanned(T::AccountId),
+        /// An account was allowed back on the job market. [who]
+        AccountUnbanned(T::AccountId),
+        /// Fund movement on a job was halted. [job]
+        JobFrozen(JobId),
+        /// Fund movement on a job was allowed again. [job]
+        JobUnfrozen(JobId),
+        /// A worker's bond was slashed to the employer. [job, worker, bond]
+        WorkerBondSlashed(JobId, T::AccountId, BalanceOf<T>),
+        /// A referrer received their commission on a job. [job, referrer, commission]
+        ReferralPaid(JobId, T::AccountId, BalanceOf<T>),
+        /// A recurring job series was created. [series, employer]
+        RecurringJobCreated(SeriesId, T::AccountId),
+        /// A recurring job series posted a new job. [series, job]
+        RecurringJobPosted(SeriesId, JobId),
+        /// A recurring job series was cancelled or ran out of funds. [series]
+        RecurringJobStopped(SeriesId),
+        /// A job was posted with encrypted details. [job, payload hash]
+        PrivateJobPosted(JobId, T::Hash),
+        /// The employer sealed the content key of a private job to its worker. [job, worker]
+        ContentKeyShared(JobId, T::AccountId),
     }
 
     // Errors inform users that something went wrong.
@@ -90,6 +309,40 @@ pub mod pallet {
         NoneValue,
         /// Errors should have helpful documentation associated with them.
         StorageOverflow,
+        /// No job exists with the given id.
+        JobNotFound,
+        /// Only the employer that posted the job may do this.
+        NotEmployer,
+        /// Only the employer or the assigned worker may do this.
+        NotParty,
+        /// The job is not in a status that allows this action.
+        InvalidJobStatus,
+        /// The worker did not apply for the job.
+        NotApplicant,
+        /// The worker already applied for the job.
+        AlreadyApplied,
+        /// The account is banned from the job market.
+        AccountBanned,
+        /// The job is frozen; its escrow cannot move.
+        JobFrozen,
+        /// The job details are longer than `MaxDetailsLen`.
+        DetailsTooLong,
+        /// No recurring job series exists with the given id.
+        SeriesNotFound,
+        /// A recurring job needs a non-zero period.
+        ZeroPeriod,
+        /// The scheduler refused the repost task.
+        ScheduleFailed,
+        /// The job was not posted with private details.
+        NotPrivate,
+        /// Content keys can only be shared with the assigned worker.
+        NotAssignedWorker,
+        /// The referrer does not hold any funds, so it could not receive a commission.
+        UnknownReferrer,
+        /// The recurring job series has no repost due.
+        RepostNotDue,
+        /// The sealed content key is longer than `MaxSealedKeyLen`.
+        SealedKeyTooLong,
     }
 
     #[pallet::hooks]
@@ -182,5 +435,479 @@ pub mod pallet {
                 }
             }
         }
+
+        /// Post a job, reserving `payment` from the employer as escrow.
+        ///
+        /// The assigned worker will have `worker_bond` reserved as collateral, which is returned
+        /// when the job completes and slashed to the employer if a dispute goes against them.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+        pub fn post_job(
+            origin: OriginFor<T>,
+            details: Vec<u8>,
+            payment: BalanceOf<T>,
+            worker_bond: BalanceOf<T>,
+        ) -> DispatchResult {
+            let employer = ensure_signed(origin)?;
+            Self::do_post_job(employer, details, payment, worker_bond)?;
+            Ok(())
+        }
+
+        /// Post a job whose details are kept off-chain, encrypted under a content key.
+        ///
+        /// Only the hash of the encrypted payload is stored. The employer later shares the
+        /// content key with the assigned worker through `share_content_key`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+        pub fn post_private_job(
+            origin: OriginFor<T>,
+            payload_hash: T::Hash,
+            payment: BalanceOf<T>,
+            worker_bond: BalanceOf<T>,
+        ) -> DispatchResult {
+            let employer = ensure_signed(origin)?;
+            let job_id = Self::do_post_job(employer, Vec::new(), payment, worker_bond)?;
+
+            <SealedDetails<T>>::insert(job_id, payload_hash);
+
+            Self::deposit_event(Event::PrivateJobPosted(job_id, payload_hash));
+            Ok(())
+        }
+
+        /// Publish the content key of a private job, sealed to the assigned worker's key.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1))]
+        pub fn share_content_key(
+            origin: OriginFor<T>,
+            job_id: JobId,
+            sealed_key: Vec<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                sealed_key.len() <= T::MaxSealedKeyLen::get() as usize,
+                Error::<T>::SealedKeyTooLong
+            );
+
+            let job = Self::job(job_id)?;
+            ensure!(job.employer == who, Error::<T>::NotEmployer);
+            ensure!(<SealedDetails<T>>::contains_key(job_id), Error::<T>::NotPrivate);
+            let worker = job.worker.ok_or(Error::<T>::NotAssignedWorker)?;
+
+            <ContentKeys<T>>::insert(job_id, &worker, sealed_key);
+
+            Self::deposit_event(Event::ContentKeyShared(job_id, worker));
+            Ok(())
+        }
+
+        /// Create a job that is posted now and reposted every `period` blocks.
+        ///
+        /// Every posting reserves escrow from the employer; the series stops by itself once a
+        /// posting can no longer be funded.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 6))]
+        #[transactional]
+        pub fn create_recurring_job(
+            origin: OriginFor<T>,
+            details: Vec<u8>,
+            payment: BalanceOf<T>,
+            worker_bond: BalanceOf<T>,
+            period: T::BlockNumber,
+        ) -> DispatchResult {
+            let employer = ensure_signed(origin)?;
+            ensure!(!period.is_zero(), Error::<T>::ZeroPeriod);
+
+            let job_id =
+                Self::do_post_job(employer.clone(), details.clone(), payment, worker_bond)?;
+
+            let series_id = <NextSeriesId<T>>::get();
+            let next = series_id.checked_add(1).ok_or(Error::<T>::StorageOverflow)?;
+            Self::schedule_repost(series_id, period)?;
+
+            <NextSeriesId<T>>::put(next);
+            <RecurringJobs<T>>::insert(series_id, RecurringJob {
+                employer: employer.clone(),
+                details,
+                payment,
+                worker_bond,
+                period,
+            });
+
+            Self::deposit_event(Event::RecurringJobCreated(series_id, employer));
+            Self::deposit_event(Event::RecurringJobPosted(series_id, job_id));
+            Ok(())
+        }
+
+        /// Post the next job of a recurring series and schedule the one after it. Dispatched by
+        /// the scheduler with the root origin, once the repost recorded for the series is due.
+        ///
+        /// If the employer is banned or cannot fund the posting, the series is stopped instead.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(6, 6))]
+        pub fn repost(origin: OriginFor<T>, series_id: SeriesId) -> DispatchResult {
+            ensure_root(origin)?;
+
+            let series = <RecurringJobs<T>>::get(series_id).ok_or(Error::<T>::SeriesNotFound)?;
+            let due = <NextRepost<T>>::get(series_id).ok_or(Error::<T>::RepostNotDue)?;
+            ensure!(frame_system::Pallet::<T>::block_number() >= due, Error::<T>::RepostNotDue);
+            <NextRepost<T>>::remove(series_id);
+
+            let posted = Self::do_post_job(
+                series.employer, series.details, series.payment, series.worker_bond,
+            );
+            match posted {
+                Ok(job_id) => {
+                    Self::deposit_event(Event::RecurringJobPosted(series_id, job_id));
+                    if Self::schedule_repost(series_id, series.period).is_err() {
+                        Self::stop_series(series_id);
+                    }
+                }
+                Err(_) => Self::stop_series(series_id),
+            }
+            Ok(())
+        }
+
+        /// Stop a recurring job series and cancel its scheduled repost. Jobs already posted are
+        /// unaffected.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 4))]
+        pub fn cancel_recurring_job(origin: OriginFor<T>, series_id: SeriesId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let series = <RecurringJobs<T>>::get(series_id).ok_or(Error::<T>::SeriesNotFound)?;
+            ensure!(series.employer == who, Error::<T>::NotEmployer);
+
+            Self::stop_series(series_id);
+            Ok(())
+        }
+
+        /// Apply for an open job, optionally naming the account that referred the worker.
+        ///
+        /// The referrer must be an existing account.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 1))]
+        pub fn apply(
+            origin: OriginFor<T>,
+            job_id: JobId,
+            referrer: Option<T::AccountId>,
+        ) -> DispatchResult {
+            let worker = ensure_signed(origin)?;
+            Self::ensure_not_banned(&worker)?;
+
+            let job = Self::job(job_id)?;
+            ensure!(job.status == JobStatus::Open, Error::<T>::InvalidJobStatus);
+            ensure!(
+                !<Applications<T>>::contains_key(job_id, &worker),
+                Error::<T>::AlreadyApplied
+            );
+            if let Some(referrer) = referrer.as_ref() {
+                ensure!(
+                    !T::Currency::total_balance(referrer).is_zero(),
+                    Error::<T>::UnknownReferrer
+                );
+            }
+
+            <Applications<T>>::insert(job_id, &worker, referrer);
+
+            Self::deposit_event(Event::JobApplied(job_id, worker));
+            Ok(())
+        }
+
+        /// Assign one of the applicants to an open job, reserving the worker bond from them.
+        ///
+        /// Applicants that were banned after applying cannot be assigned, and neither can anyone
+        /// be assigned to a frozen job.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 2))]
+        pub fn assign(origin: OriginFor<T>, job_id: JobId, worker: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_not_banned(&worker)?;
+
+            let mut job = Self::job(job_id)?;
+            ensure!(job.employer == who, Error::<T>::NotEmployer);
+            ensure!(job.status == JobStatus::Open, Error::<T>::InvalidJobStatus);
+            Self::ensure_not_frozen(job_id)?;
+            let referrer = <Applications<T>>::get(job_id, &worker).ok_or(Error::<T>::NotApplicant)?;
+
+            T::Currency::reserve(&worker, job.worker_bond)?;
+
+            job.worker = Some(worker.clone());
+            job.referrer = referrer;
+            job.status = JobStatus::Assigned;
+            <Jobs<T>>::insert(job_id, job);
+
+            Self::deposit_event(Event::JobAssigned(job_id, worker));
+            Ok(())
+        }
+
+        /// Approve the work on an assigned job, releasing the escrow and the bond to the worker.
+        ///
+        /// If the worker was referred, the referral commission is paid out of the escrow.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 9))]
+        #[transactional]
+        pub fn approve(origin: OriginFor<T>, job_id: JobId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut job = Self::job(job_id)?;
+            ensure!(job.employer == who, Error::<T>::NotEmployer);
+            ensure!(job.status == JobStatus::Assigned, Error::<T>::InvalidJobStatus);
+            Self::ensure_not_frozen(job_id)?;
+
+            let worker = job.worker.clone().ok_or(Error::<T>::InvalidJobStatus)?;
+            let payment = Self::pay_out(job_id, &job, &worker)?;
+            Self::clear_private_job(job_id, &job);
+
+            job.status = JobStatus::Completed;
+            <Jobs<T>>::insert(job_id, job);
+
+            Self::deposit_event(Event::JobCompleted(job_id, worker, payment));
+            Ok(())
+        }
+
+        /// Withdraw an open job and refund its escrow.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+        pub fn cancel(origin: OriginFor<T>, job_id: JobId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut job = Self::job(job_id)?;
+            ensure!(job.employer == who, Error::<T>::NotEmployer);
+            ensure!(job.status == JobStatus::Open, Error::<T>::InvalidJobStatus);
+            Self::ensure_not_frozen(job_id)?;
+
+            T::Currency::unreserve(&job.employer, job.payment);
+            Self::clear_private_job(job_id, &job);
+
+            job.status = JobStatus::Cancelled;
+            <Jobs<T>>::insert(job_id, job);
+
+            Self::deposit_event(Event::JobCancelled(job_id));
+            Ok(())
+        }
+
+        /// Dispute an assigned job. Can be called by the employer or the worker.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn dispute(origin: OriginFor<T>, job_id: JobId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut job = Self::job(job_id)?;
+            ensure!(
+                job.employer == who || job.worker.as_ref() == Some(&who),
+                Error::<T>::NotParty
+            );
+            ensure!(job.status == JobStatus::Assigned, Error::<T>::InvalidJobStatus);
+
+            job.status = JobStatus::Disputed;
+            <Jobs<T>>::insert(job_id, job);
+
+            Self::deposit_event(Event::JobDisputed(job_id, who));
+            Ok(())
+        }
+
+        /// Settle a disputed job, paying the worker or refunding the employer.
+        ///
+        /// When the dispute goes against the worker their bond is slashed to the employer.
+        /// Resolving also lifts a freeze on the job.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 10))]
+        #[transactional]
+        pub fn resolve(origin: OriginFor<T>, job_id: JobId, pay_worker: bool) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            let mut job = Self::job(job_id)?;
+            ensure!(job.status == JobStatus::Disputed, Error::<T>::InvalidJobStatus);
+
+            match (pay_worker, job.worker.as_ref()) {
+                (true, Some(worker)) => {
+                    Self::pay_out(job_id, &job, worker)?;
+                }
+                (false, Some(worker)) => {
+                    let not_moved = T::Currency::repatriate_reserved(
+                        worker, &job.employer, job.worker_bond, BalanceStatus::Free,
+                    )?;
+                    T::Currency::unreserve(&job.employer, job.payment);
+                    // Only what was still reserved from the worker can be slashed.
+                    let slashed = job.worker_bond - not_moved;
+                    Self::deposit_event(Event::WorkerBondSlashed(job_id, worker.clone(), slashed));
+                }
+                _ => {
+                    T::Currency::unreserve(&job.employer, job.payment);
+                }
+            }
+
+            Self::clear_private_job(job_id, &job);
+            job.status = JobStatus::Resolved;
+            <Jobs<T>>::insert(job_id, job);
+            <Frozen<T>>::remove(job_id);
+
+            Self::deposit_event(Event::JobResolved(job_id, pay_worker));
+            Ok(())
+        }
+
+        /// Ban an account from posting or applying for jobs.
+        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+        pub fn ban(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            <Banned<T>>::insert(&who, ());
+
+            Self::deposit_event(Event::AccountBanned(who));
+            Ok(())
+        }
+
+        /// Lift the ban on an account.
+        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+        pub fn unban(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            <Banned<T>>::remove(&who);
+
+            Self::deposit_event(Event::AccountUnbanned(who));
+            Ok(())
+        }
+
+        /// Halt all fund movement on a job.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn freeze(origin: OriginFor<T>, job_id: JobId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(<Jobs<T>>::contains_key(job_id), Error::<T>::JobNotFound);
+
+            <Frozen<T>>::insert(job_id, ());
+
+            Self::deposit_event(Event::JobFrozen(job_id));
+            Ok(())
+        }
+
+        /// Allow fund movement on a frozen job again.
+        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+        pub fn unfreeze(origin: OriginFor<T>, job_id: JobId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            <Frozen<T>>::remove(job_id);
+
+            Self::deposit_event(Event::JobUnfrozen(job_id));
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Remove and return the jobs completed in block `number`, for the MMR leaf of its child.
+        ///
+        /// The completions are keyed by block, so they are not lost whichever order the hooks of
+        /// the MMR and of this pallet run in.
+        pub fn take_completions(number: T::BlockNumber) -> Vec<CompletionOf<T>> {
+            <Completions<T>>::take(number)
+        }
+
+        /// Reserve the escrow from `employer` and store a new open job.
+        fn do_post_job(
+            employer: T::AccountId,
+            details: Vec<u8>,
+            payment: BalanceOf<T>,
+            worker_bond: BalanceOf<T>,
+        ) -> Result<JobId, DispatchError> {
+            Self::ensure_not_banned(&employer)?;
+            ensure!(details.len() <= T::MaxDetailsLen::get() as usize, Error::<T>::DetailsTooLong);
+
+            let job_id = <NextJobId<T>>::get();
+            let next = job_id.checked_add(1).ok_or(Error::<T>::StorageOverflow)?;
+
+            T::Currency::reserve(&employer, payment)?;
+
+            <NextJobId<T>>::put(next);
+            <Jobs<T>>::insert(job_id, Job {
+                employer: employer.clone(),
+                worker: None,
+                payment,
+                worker_bond,
+                referrer: None,
+                referral_commission: T::ReferralCommission::get(),
+                status: JobStatus::Open,
+                details,
+            });
+
+            Self::deposit_event(Event::JobPosted(job_id, employer, payment));
+            Ok(job_id)
+        }
+
+        /// Schedule the next repost of a series `period` blocks from now and record when it is
+        /// due, so that only that task can post the job.
+        fn schedule_repost(series_id: SeriesId, period: T::BlockNumber) -> DispatchResult {
+            let due = frame_system::Pallet::<T>::block_number().saturating_add(period);
+            T::Scheduler::schedule_named(
+                Self::repost_task(series_id, due),
+                DispatchTime::At(due),
+                None,
+                LOWEST_PRIORITY,
+                frame_system::RawOrigin::Root.into(),
+                Call::repost(series_id).into(),
+            ).map_err(|_| Error::<T>::ScheduleFailed)?;
+            <NextRepost<T>>::insert(series_id, due);
+            Ok(())
+        }
+
+        /// The name of the scheduler task reposting a series at block `due`.
+        pub(crate) fn repost_task(series_id: SeriesId, due: T::BlockNumber) -> Vec<u8> {
+            (&b"labor/repost"[..], series_id, due).encode()
+        }
+
+        fn stop_series(series_id: SeriesId) {
+            <RecurringJobs<T>>::remove(series_id);
+            if let Some(due) = <NextRepost<T>>::take(series_id) {
+                // The task is gone if the scheduler already dispatched it.
+                let _ = T::Scheduler::cancel_named(Self::repost_task(series_id, due));
+            }
+            Self::deposit_event(Event::RecurringJobStopped(series_id));
+        }
+
+        fn job(job_id: JobId) -> Result<JobOf<T>, Error<T>> {
+            <Jobs<T>>::get(job_id).ok_or(Error::<T>::JobNotFound)
+        }
+
+        /// Release the escrow of `job` to `worker`, minus the referral commission, and return
+        /// the worker bond. Returns the amount the worker received and records the completion.
+        ///
+        /// A referrer whose account has been reaped since they were named gets no commission;
+        /// it goes to the worker, so that the job can still be paid.
+        fn pay_out(
+            job_id: JobId,
+            job: &JobOf<T>,
+            worker: &T::AccountId,
+        ) -> Result<BalanceOf<T>, DispatchError> {
+            let mut payment = job.payment;
+            let referrer = job.referrer.as_ref()
+                .filter(|referrer| !T::Currency::total_balance(referrer).is_zero());
+            if let Some(referrer) = referrer {
+                let commission = job.referral_commission * job.payment;
+                let not_moved = T::Currency::repatriate_reserved(
+                    &job.employer, referrer, commission, BalanceStatus::Free,
+                )?;
+                payment -= commission;
+                // Only what was still reserved from the employer can be paid.
+                let paid = commission - not_moved;
+                Self::deposit_event(Event::ReferralPaid(job_id, referrer.clone(), paid));
+            }
+            let not_moved = T::Currency::repatriate_reserved(
+                &job.employer, worker, payment, BalanceStatus::Free,
+            )?;
+            payment -= not_moved;
+            T::Currency::unreserve(worker, job.worker_bond);
+            Self::record_completion(Completion { job: job_id, worker: worker.clone(), payment });
+            Ok(payment)
+        }
+
+        /// Add a completion to those of the current block, for the MMR leaf of the next one.
+        fn record_completion(completion: CompletionOf<T>) {
+            let now = frame_system::Pallet::<T>::block_number();
+            <CompletedAt<T>>::insert(completion.job, now);
+            <Completions<T>>::append(now, completion);
+        }
+
+        /// Remove the payload hash and the sealed content key of a settled private job.
+        fn clear_private_job(job_id: JobId, job: &JobOf<T>) {
+            <SealedDetails<T>>::remove(job_id);
+            if let Some(worker) = job.worker.as_ref() {
+                <ContentKeys<T>>::remove(job_id, worker);
+            }
+        }
+
+        fn ensure_not_banned(who: &T::AccountId) -> Result<(), Error<T>> {
+            ensure!(!<Banned<T>>::contains_key(who), Error::<T>::AccountBanned);
+            Ok(())
+        }
+
+        fn ensure_not_frozen(job_id: JobId) -> Result<(), Error<T>> {
+            ensure!(!<Frozen<T>>::contains_key(job_id), Error::<T>::JobFrozen);
+            Ok(())
+        }
     }
 }
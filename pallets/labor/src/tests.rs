@@ -1,13 +1,15 @@
-use crate::{Error, mock::*};
-use frame_support::{assert_ok, assert_noop};
+use crate::{Completion, Error, JobStatus, mock::*};
+use frame_support::{assert_ok, assert_noop, traits::{ReservableCurrency, schedule::Named}};
+use sp_core::H256;
+use sp_runtime::DispatchError;
 
 #[test]
 fn it_works_for_default_value() {
 	new_test_ext().execute_with(|| {
 		// Dispatch a signed extrinsic.
-		assert_ok!(LaborModule::do_something(Origin::signed(1), 42));
+		assert_ok!(LaborModule::do_some_number(Origin::signed(1), 42));
 		// Read pallet storage and assert an expected result.
-		assert_eq!(LaborModule::something(), Some(42));
+		assert_eq!(LaborModule::some_number(), Some(42));
 	});
 }
 
@@ -21,3 +23,351 @@ fn correct_error_for_none_value() {
 		);
 	});
 }
+
+/// Post a job from account 1 paying 50 with a bond of 20 and assign account 2 to it.
+fn assigned_job() -> u32 {
+	assert_ok!(LaborModule::post_job(Origin::signed(1), b"paint fence".to_vec(), 50, 20));
+	assert_ok!(LaborModule::apply(Origin::signed(2), 0, None));
+	assert_ok!(LaborModule::assign(Origin::signed(1), 0, 2));
+	0
+}
+
+#[test]
+fn job_escrow_is_released_on_approval() {
+	new_test_ext().execute_with(|| {
+		let job = assigned_job();
+		assert_eq!(Balances::reserved_balance(1), 50);
+
+		assert_eq!(Balances::reserved_balance(2), 20);
+
+		assert_ok!(LaborModule::approve(Origin::signed(1), job));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(2), 150);
+		assert_eq!(LaborModule::jobs(job).unwrap().status, JobStatus::Completed);
+	});
+}
+
+#[test]
+fn referrer_receives_commission_on_completion() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(LaborModule::post_job(Origin::signed(1), b"paint fence".to_vec(), 50, 0));
+		assert_ok!(LaborModule::apply(Origin::signed(2), 0, Some(3)));
+		assert_ok!(LaborModule::assign(Origin::signed(1), 0, 2));
+		assert_eq!(LaborModule::jobs(0).unwrap().referrer, Some(3));
+
+		assert_ok!(LaborModule::approve(Origin::signed(1), 0));
+		assert_eq!(Balances::free_balance(3), 105);
+		assert_eq!(Balances::free_balance(2), 145);
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn referrers_must_exist() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(LaborModule::post_job(Origin::signed(1), b"paint fence".to_vec(), 50, 0));
+		assert_noop!(
+			LaborModule::apply(Origin::signed(2), 0, Some(4)),
+			Error::<Test>::UnknownReferrer
+		);
+	});
+}
+
+#[test]
+fn reaped_referrer_does_not_block_payment() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(LaborModule::post_job(Origin::signed(1), b"paint fence".to_vec(), 50, 0));
+		assert_ok!(LaborModule::apply(Origin::signed(2), 0, Some(3)));
+		assert_ok!(LaborModule::assign(Origin::signed(1), 0, 2));
+		assert_ok!(Balances::transfer(Origin::signed(3), 1, 100));
+
+		assert_ok!(LaborModule::approve(Origin::signed(1), 0));
+		assert_eq!(Balances::free_balance(3), 0);
+		assert_eq!(Balances::free_balance(2), 150);
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn failed_approval_moves_no_funds() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(LaborModule::post_job(Origin::signed(1), b"paint fence".to_vec(), 50, 0));
+		assert_ok!(LaborModule::apply(Origin::signed(2), 0, Some(3)));
+		assert_ok!(LaborModule::assign(Origin::signed(1), 0, 2));
+		// The worker empties their account, so the payment cannot reach them.
+		assert_ok!(Balances::transfer(Origin::signed(2), 1, 100));
+
+		// The referral commission paid before the failure is rolled back as well.
+		assert_noop!(
+			LaborModule::approve(Origin::signed(1), 0),
+			pallet_balances::Error::<Test>::DeadAccount
+		);
+	});
+}
+
+#[test]
+fn banned_accounts_cannot_post_or_apply() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(LaborModule::post_job(Origin::signed(1), b"paint fence".to_vec(), 50, 0));
+		assert_noop!(LaborModule::ban(Origin::signed(1), 2), DispatchError::BadOrigin);
+		assert_ok!(LaborModule::ban(Origin::signed(Admin::get()), 2));
+
+		assert_noop!(LaborModule::apply(Origin::signed(2), 0, None), Error::<Test>::AccountBanned);
+		assert_noop!(
+			LaborModule::post_job(Origin::signed(2), b"mow lawn".to_vec(), 10, 0),
+			Error::<Test>::AccountBanned
+		);
+
+		assert_ok!(LaborModule::unban(Origin::signed(Admin::get()), 2));
+		assert_ok!(LaborModule::apply(Origin::signed(2), 0, None));
+	});
+}
+
+#[test]
+fn applicants_banned_after_applying_cannot_be_assigned() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(LaborModule::post_job(Origin::signed(1), b"paint fence".to_vec(), 50, 0));
+		assert_ok!(LaborModule::apply(Origin::signed(2), 0, None));
+		assert_ok!(LaborModule::ban(Origin::signed(Admin::get()), 2));
+
+		assert_noop!(LaborModule::assign(Origin::signed(1), 0, 2), Error::<Test>::AccountBanned);
+		assert_eq!(LaborModule::jobs(0).unwrap().status, JobStatus::Open);
+	});
+}
+
+#[test]
+fn frozen_jobs_block_fund_movement_until_resolved() {
+	new_test_ext().execute_with(|| {
+		let job = assigned_job();
+		assert_ok!(LaborModule::freeze(Origin::signed(Admin::get()), job));
+		assert_noop!(LaborModule::approve(Origin::signed(1), job), Error::<Test>::JobFrozen);
+
+		assert_ok!(LaborModule::dispute(Origin::signed(2), job));
+		assert_ok!(LaborModule::resolve(Origin::signed(Admin::get()), job, false));
+		assert_eq!(LaborModule::frozen(job), None);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(LaborModule::jobs(job).unwrap().status, JobStatus::Resolved);
+	});
+}
+
+#[test]
+fn frozen_jobs_cannot_be_assigned() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(LaborModule::post_job(Origin::signed(1), b"paint fence".to_vec(), 50, 20));
+		assert_ok!(LaborModule::apply(Origin::signed(2), 0, None));
+		assert_ok!(LaborModule::freeze(Origin::signed(Admin::get()), 0));
+
+		assert_noop!(LaborModule::assign(Origin::signed(1), 0, 2), Error::<Test>::JobFrozen);
+		assert_eq!(Balances::reserved_balance(2), 0);
+
+		assert_ok!(LaborModule::unfreeze(Origin::signed(Admin::get()), 0));
+		assert_ok!(LaborModule::assign(Origin::signed(1), 0, 2));
+		assert_eq!(Balances::reserved_balance(2), 20);
+	});
+}
+
+#[test]
+fn job_details_are_bounded() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			LaborModule::post_job(Origin::signed(1), vec![b'x'; 33], 50, 0),
+			Error::<Test>::DetailsTooLong
+		);
+		assert_ok!(LaborModule::post_job(Origin::signed(1), vec![b'x'; 32], 50, 0));
+	});
+}
+
+#[test]
+fn completions_are_recorded_for_the_mmr_leaf() {
+	new_test_ext().execute_with(|| {
+		let job = assigned_job();
+		assert_ok!(LaborModule::approve(Origin::signed(1), job));
+		let completion = Completion { job, worker: 2, payment: 50 };
+		assert_eq!(LaborModule::completions_in(1), vec![completion]);
+		assert_eq!(LaborModule::completed_at(job), Some(1));
+
+		System::set_block_number(2);
+		assert_ok!(LaborModule::post_job(Origin::signed(1), b"mow lawn".to_vec(), 30, 0));
+		assert_ok!(LaborModule::apply(Origin::signed(3), 1, None));
+		assert_ok!(LaborModule::assign(Origin::signed(1), 1, 3));
+		assert_ok!(LaborModule::dispute(Origin::signed(3), 1));
+		assert_ok!(LaborModule::resolve(Origin::signed(Admin::get()), 1, true));
+		let completion = Completion { job: 1, worker: 3, payment: 30 };
+		assert_eq!(LaborModule::completions_in(2), vec![completion]);
+		assert_eq!(LaborModule::completed_at(1), Some(2));
+	});
+}
+
+#[test]
+fn completions_of_consecutive_blocks_are_kept_until_taken() {
+	new_test_ext().execute_with(|| {
+		let job = assigned_job();
+		assert_ok!(LaborModule::approve(Origin::signed(1), job));
+
+		// Block 2 completes a job before the MMR leaf takes the completions of block 1.
+		System::set_block_number(2);
+		assert_ok!(LaborModule::post_job(Origin::signed(1), b"mow lawn".to_vec(), 30, 0));
+		assert_ok!(LaborModule::apply(Origin::signed(3), 1, None));
+		assert_ok!(LaborModule::assign(Origin::signed(1), 1, 3));
+		assert_ok!(LaborModule::approve(Origin::signed(1), 1));
+
+		let first = Completion { job, worker: 2, payment: 50 };
+		let second = Completion { job: 1, worker: 3, payment: 30 };
+		assert_eq!(LaborModule::take_completions(1), vec![first]);
+		assert!(LaborModule::completions_in(1).is_empty());
+		assert_eq!(LaborModule::take_completions(2), vec![second]);
+		assert!(LaborModule::take_completions(2).is_empty());
+	});
+}
+
+#[test]
+fn worker_bond_is_slashed_when_dispute_goes_against_worker() {
+	new_test_ext().execute_with(|| {
+		let job = assigned_job();
+
+		assert_ok!(LaborModule::dispute(Origin::signed(1), job));
+		assert_ok!(LaborModule::resolve(Origin::signed(Admin::get()), job, false));
+		assert_eq!(Balances::free_balance(1), 120);
+		assert_eq!(Balances::free_balance(2), 80);
+		assert_eq!(Balances::reserved_balance(2), 0);
+	});
+}
+
+#[test]
+fn only_the_reserved_part_of_the_bond_is_reported_slashed() {
+	new_test_ext().execute_with(|| {
+		let job = assigned_job();
+		// Part of the bond was slashed elsewhere before the dispute was resolved.
+		Balances::slash_reserved(&2, 15);
+
+		assert_ok!(LaborModule::dispute(Origin::signed(1), job));
+		assert_ok!(LaborModule::resolve(Origin::signed(Admin::get()), job, false));
+		assert_eq!(Balances::free_balance(1), 105);
+		let slashed: Event = crate::Event::<Test>::WorkerBondSlashed(job, 2, 5).into();
+		assert!(System::events().iter().any(|record| record.event == slashed));
+	});
+}
+
+#[test]
+fn assignment_fails_without_funds_for_bond() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(LaborModule::post_job(Origin::signed(1), b"audit".to_vec(), 50, 500));
+		assert_ok!(LaborModule::apply(Origin::signed(2), 0, None));
+		assert!(LaborModule::assign(Origin::signed(1), 0, 2).is_err());
+		assert_eq!(LaborModule::jobs(0).unwrap().status, JobStatus::Open);
+	});
+}
+
+#[test]
+fn recurring_job_reposts_until_funds_run_out() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(LaborModule::create_recurring_job(
+			Origin::signed(1), b"night shift".to_vec(), 30, 0, 10,
+		));
+		assert_eq!(LaborModule::next_job_id(), 1);
+
+		run_to_block(15);
+		assert_eq!(LaborModule::next_job_id(), 2);
+		assert_eq!(Balances::reserved_balance(1), 60);
+
+		// The fourth posting cannot be funded and stops the series.
+		run_to_block(50);
+		assert_eq!(LaborModule::next_job_id(), 3);
+		assert_eq!(LaborModule::recurring_jobs(0), None);
+		assert_eq!(Balances::reserved_balance(1), 90);
+	});
+}
+
+#[test]
+fn cancelled_recurring_job_stops_reposting() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(LaborModule::create_recurring_job(
+			Origin::signed(1), b"night shift".to_vec(), 10, 0, 10,
+		));
+		assert_noop!(
+			LaborModule::cancel_recurring_job(Origin::signed(2), 0),
+			Error::<Test>::NotEmployer
+		);
+		assert_ok!(LaborModule::cancel_recurring_job(Origin::signed(1), 0));
+		// The scheduled repost was cancelled, so there is no task left to cancel.
+		let task = LaborModule::repost_task(0, 11);
+		assert!(<Scheduler as Named<u64, Call, OriginCaller>>::cancel_named(task).is_err());
+
+		run_to_block(50);
+		assert_eq!(LaborModule::next_job_id(), 1);
+	});
+}
+
+#[test]
+fn reposts_are_only_dispatched_by_the_scheduler() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(LaborModule::create_recurring_job(
+			Origin::signed(1), b"night shift".to_vec(), 10, 0, 10,
+		));
+		assert_eq!(LaborModule::next_repost(0), Some(11));
+		assert_noop!(LaborModule::repost(Origin::signed(1), 0), DispatchError::BadOrigin);
+		assert_noop!(LaborModule::repost(Origin::root(), 0), Error::<Test>::RepostNotDue);
+
+		// Once the scheduler posted the job, the repost is no longer due.
+		run_to_block(11);
+		assert_eq!(LaborModule::next_job_id(), 2);
+		assert_eq!(LaborModule::next_repost(0), Some(21));
+		assert_noop!(LaborModule::repost(Origin::root(), 0), Error::<Test>::RepostNotDue);
+	});
+}
+
+#[test]
+fn private_job_keys_are_shared_with_assigned_worker_only() {
+	new_test_ext().execute_with(|| {
+		let payload_hash = H256::repeat_byte(7);
+		assert_ok!(LaborModule::post_private_job(Origin::signed(1), payload_hash, 50, 0));
+		assert_eq!(LaborModule::sealed_details(0), Some(payload_hash));
+		assert!(LaborModule::jobs(0).unwrap().details.is_empty());
+
+		assert_noop!(
+			LaborModule::share_content_key(Origin::signed(1), 0, vec![1, 2, 3]),
+			Error::<Test>::NotAssignedWorker
+		);
+
+		assert_ok!(LaborModule::apply(Origin::signed(2), 0, None));
+		assert_ok!(LaborModule::assign(Origin::signed(1), 0, 2));
+		assert_noop!(
+			LaborModule::share_content_key(Origin::signed(2), 0, vec![1, 2, 3]),
+			Error::<Test>::NotEmployer
+		);
+		assert_noop!(
+			LaborModule::share_content_key(Origin::signed(1), 0, vec![0; 129]),
+			Error::<Test>::SealedKeyTooLong
+		);
+		assert_ok!(LaborModule::share_content_key(Origin::signed(1), 0, vec![1, 2, 3]));
+		assert_eq!(LaborModule::content_keys(0, 2), Some(vec![1, 2, 3]));
+	});
+}
+
+#[test]
+fn private_job_data_is_removed_once_settled() {
+	new_test_ext().execute_with(|| {
+		let payload_hash = H256::repeat_byte(7);
+		assert_ok!(LaborModule::post_private_job(Origin::signed(1), payload_hash, 20, 0));
+		assert_ok!(LaborModule::apply(Origin::signed(2), 0, None));
+		assert_ok!(LaborModule::assign(Origin::signed(1), 0, 2));
+		assert_ok!(LaborModule::share_content_key(Origin::signed(1), 0, vec![1, 2, 3]));
+		assert_ok!(LaborModule::approve(Origin::signed(1), 0));
+		assert_eq!(LaborModule::sealed_details(0), None);
+		assert_eq!(LaborModule::content_keys(0, 2), None);
+
+		assert_ok!(LaborModule::post_private_job(Origin::signed(1), payload_hash, 20, 0));
+		assert_ok!(LaborModule::cancel(Origin::signed(1), 1));
+		assert_eq!(LaborModule::sealed_details(1), None);
+
+		assert_ok!(LaborModule::post_private_job(Origin::signed(1), payload_hash, 20, 0));
+		assert_ok!(LaborModule::apply(Origin::signed(2), 2, None));
+		assert_ok!(LaborModule::assign(Origin::signed(1), 2, 2));
+		assert_ok!(LaborModule::share_content_key(Origin::signed(1), 2, vec![1, 2, 3]));
+		assert_ok!(LaborModule::dispute(Origin::signed(2), 2));
+		assert_ok!(LaborModule::resolve(Origin::signed(Admin::get()), 2, false));
+		assert_eq!(LaborModule::sealed_details(2), None);
+		assert_eq!(LaborModule::content_keys(2, 2), None);
+	});
+}
@@ -0,0 +1,119 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Slot and epoch durations chosen at genesis.
+//!
+//! BABE cannot change its slot or epoch duration once a chain runs, but nothing forces them to be
+//! the same for every chain a runtime backs. This pallet stores the values given in the genesis
+//! config, so one runtime build can run a fast local chain as well as a production chain. Chains
+//! whose genesis does not set them use the defaults of the runtime.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// Slot duration in milliseconds, unless set at genesis.
+        type DefaultSlotDuration: Get<u64>;
+
+        /// Epoch duration in slots, unless set at genesis.
+        type DefaultEpochDuration: Get<u64>;
+    }
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::type_value]
+    pub fn DefaultSlotDuration<T: Config>() -> u64 {
+        T::DefaultSlotDuration::get()
+    }
+
+    #[pallet::type_value]
+    pub fn DefaultEpochDuration<T: Config>() -> u64 {
+        T::DefaultEpochDuration::get()
+    }
+
+    /// Slot duration in milliseconds.
+    #[pallet::storage]
+    #[pallet::getter(fn slot_duration)]
+    pub type SlotDuration<T> = StorageValue<_, u64, ValueQuery, DefaultSlotDuration<T>>;
+
+    /// Epoch duration in slots.
+    #[pallet::storage]
+    #[pallet::getter(fn epoch_duration)]
+    pub type EpochDuration<T> = StorageValue<_, u64, ValueQuery, DefaultEpochDuration<T>>;
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {}
+
+    impl<T: Config> Pallet<T> {
+        /// The number of slots in `millis` milliseconds, at least one. Periods that the runtime
+        /// expresses in wall clock time are converted with it.
+        pub fn slots_in(millis: u64) -> u64 {
+            (millis / Self::slot_duration().max(1)).max(1)
+        }
+    }
+
+    /// Durations of zero leave the runtime defaults in place.
+    #[pallet::genesis_config]
+    #[derive(Default)]
+    pub struct GenesisConfig {
+        /// Slot duration in milliseconds. Must be even, the timestamp pallet enforces half of it
+        /// between blocks.
+        pub slot_duration: u64,
+        /// Epoch duration in slots.
+        pub epoch_duration: u64,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig {
+        fn build(&self) {
+            if self.slot_duration != 0 {
+                assert!(self.slot_duration % 2 == 0, "Slot duration must be even");
+                SlotDuration::<T>::put(self.slot_duration);
+            }
+            if self.epoch_duration != 0 {
+                EpochDuration::<T>::put(self.epoch_duration);
+            }
+        }
+    }
+}
+
+/// The slot duration, for `ExpectedBlockTime` of BABE.
+pub struct SlotDurationOf<T>(frame_support::sp_std::marker::PhantomData<T>);
+
+impl<T: Config, Moment: From<u64>> frame_support::traits::Get<Moment> for SlotDurationOf<T> {
+    fn get() -> Moment {
+        Pallet::<T>::slot_duration().into()
+    }
+}
+
+/// Half the slot duration, for `MinimumPeriod` of the timestamp pallet. BABE derives its slot
+/// duration from it.
+pub struct MinimumPeriodOf<T>(frame_support::sp_std::marker::PhantomData<T>);
+
+impl<T: Config, Moment: From<u64>> frame_support::traits::Get<Moment> for MinimumPeriodOf<T> {
+    fn get() -> Moment {
+        (Pallet::<T>::slot_duration() / 2).into()
+    }
+}
+
+/// The epoch duration, for `EpochDuration` of BABE.
+pub struct EpochDurationOf<T>(frame_support::sp_std::marker::PhantomData<T>);
+
+impl<T: Config> frame_support::traits::Get<u64> for EpochDurationOf<T> {
+    fn get() -> u64 {
+        Pallet::<T>::epoch_duration()
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{mock::*, EpochDurationOf, GenesisConfig, MinimumPeriodOf, SlotDurationOf};
+use frame_support::traits::Get;
+
+#[test]
+fn runtime_defaults_apply_without_genesis_values() {
+	new_test_ext(GenesisConfig::default()).execute_with(|| {
+		assert_eq!(Timing::slot_duration(), 3000);
+		assert_eq!(Timing::epoch_duration(), 200);
+		assert_eq!(<MinimumPeriodOf<Test> as Get<u64>>::get(), 1500);
+	});
+}
+
+#[test]
+fn genesis_values_override_runtime_defaults() {
+	let timing = GenesisConfig { slot_duration: 1000, epoch_duration: 10 };
+	new_test_ext(timing).execute_with(|| {
+		assert_eq!(<SlotDurationOf<Test> as Get<u64>>::get(), 1000);
+		assert_eq!(<MinimumPeriodOf<Test> as Get<u64>>::get(), 500);
+		assert_eq!(EpochDurationOf::<Test>::get(), 10);
+	});
+}
+
+#[test]
+fn wall_clock_periods_follow_the_slot_duration() {
+	new_test_ext(GenesisConfig::default()).execute_with(|| {
+		assert_eq!(Timing::slots_in(60_000), 20);
+	});
+	new_test_ext(GenesisConfig { slot_duration: 1000, epoch_duration: 0 }).execute_with(|| {
+		assert_eq!(Timing::slots_in(60_000), 60);
+		assert_eq!(Timing::slots_in(1), 1);
+	});
+}
+
+#[test]
+#[should_panic(expected = "Slot duration must be even")]
+fn odd_slot_duration_is_rejected() {
+	new_test_ext(GenesisConfig { slot_duration: 1001, epoch_duration: 0 });
+}